@@ -0,0 +1,254 @@
+//! 按 BOM 识别文本编码，并将 UTF-16/UTF-32 流式解码为 UTF-8。
+
+use std::io;
+
+/// UTF-8 的 BOM。
+pub const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// 由 BOM 识别出的编码。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding {
+    /// 按开头的 BOM 识别编码，返回编码与 BOM 长度。
+    ///
+    /// UTF-32LE 的 BOM 以 UTF-16LE 的 BOM 开头，须先判断。
+    pub fn detect(head: &[u8]) -> Option<(Encoding, usize)> {
+        const BOMS: [(&[u8], Encoding); 5] = [
+            (b"\xff\xfe\x00\x00", Encoding::Utf32Le),
+            (b"\x00\x00\xfe\xff", Encoding::Utf32Be),
+            (UTF8_BOM, Encoding::Utf8),
+            (b"\xff\xfe", Encoding::Utf16Le),
+            (b"\xfe\xff", Encoding::Utf16Be),
+        ];
+        BOMS.iter()
+            .find(|(bom, _)| head.starts_with(bom))
+            .map(|&(bom, enc)| (enc, bom.len()))
+    }
+
+    /// 每个码元的字节数；UTF-8 为 1。
+    pub fn unit_len(self) -> usize {
+        match self {
+            Encoding::Utf8 => 1,
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Utf32Le | Encoding::Utf32Be => 4,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+        }
+    }
+}
+
+fn invalid(encoding: Encoding, what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} 数据无效: {}", encoding.name(), what),
+    )
+}
+
+/// UTF-16/UTF-32 到 UTF-8 的流式解码器：不完整的码元与未配对的高位代理项留到下一块。
+/// 遇到无效数据时报错而不是替换，避免悄悄改变文件内容。
+#[derive(Debug)]
+pub struct Decoder {
+    encoding: Encoding,
+    /// 上一块末尾不足一个码元的字节。
+    partial: Vec<u8>,
+    high_surrogate: Option<u16>,
+}
+
+impl Decoder {
+    pub fn new(encoding: Encoding) -> Self {
+        debug_assert!(encoding != Encoding::Utf8, "UTF-8 无需解码");
+        Decoder {
+            encoding,
+            partial: Vec::with_capacity(4),
+            high_surrogate: None,
+        }
+    }
+
+    /// 解码一块输入，UTF-8 结果追加到 `out`。
+    pub fn decode(&mut self, mut input: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        let unit = self.encoding.unit_len();
+        if !self.partial.is_empty() {
+            let take = (unit - self.partial.len()).min(input.len());
+            self.partial.extend_from_slice(&input[..take]);
+            input = &input[take..];
+            if self.partial.len() < unit {
+                return Ok(());
+            }
+            let bytes = std::mem::take(&mut self.partial);
+            self.unit(&bytes, out)?;
+            self.partial = bytes;
+            self.partial.clear();
+        }
+        let whole = input.len() / unit * unit;
+        for bytes in input[..whole].chunks_exact(unit) {
+            self.unit(bytes, out)?;
+        }
+        self.partial.extend_from_slice(&input[whole..]);
+        Ok(())
+    }
+
+    /// 输入结束：残留的字节或未配对的代理项说明数据被截断。
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.partial.is_empty() {
+            return Err(invalid(self.encoding, "末尾的码元不完整"));
+        }
+        if self.high_surrogate.is_some() {
+            return Err(invalid(self.encoding, "末尾的代理项未配对"));
+        }
+        Ok(())
+    }
+
+    fn unit(&mut self, bytes: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        let c = match self.encoding {
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let pair = [bytes[0], bytes[1]];
+                let u = if self.encoding == Encoding::Utf16Le {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                };
+                match (self.high_surrogate.take(), u) {
+                    (Some(hi), 0xdc00..=0xdfff) => {
+                        let code =
+                            0x10000 + ((u32::from(hi) - 0xd800) << 10) + (u32::from(u) - 0xdc00);
+                        char::from_u32(code)
+                    }
+                    (Some(_), _) => {
+                        return Err(invalid(self.encoding, "高位代理项后缺少低位代理项"))
+                    }
+                    (None, 0xd800..=0xdbff) => {
+                        self.high_surrogate = Some(u);
+                        return Ok(());
+                    }
+                    (None, u) => char::from_u32(u32::from(u)),
+                }
+            }
+            Encoding::Utf32Le | Encoding::Utf32Be => {
+                let quad = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                char::from_u32(if self.encoding == Encoding::Utf32Le {
+                    u32::from_le_bytes(quad)
+                } else {
+                    u32::from_be_bytes(quad)
+                })
+            }
+            Encoding::Utf8 => unreachable!("UTF-8 无需解码"),
+        };
+        let c = c.ok_or_else(|| invalid(self.encoding, "无效的码点"))?;
+        let mut buf = [0u8; 4];
+        out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(text: &str, encoding: Encoding) -> Vec<u8> {
+        match encoding {
+            Encoding::Utf8 => text.as_bytes().to_vec(),
+            Encoding::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Encoding::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Encoding::Utf32Le => text
+                .chars()
+                .flat_map(|c| (c as u32).to_le_bytes())
+                .collect(),
+            Encoding::Utf32Be => text
+                .chars()
+                .flat_map(|c| (c as u32).to_be_bytes())
+                .collect(),
+        }
+    }
+
+    const WIDE: [Encoding; 4] = [
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+        Encoding::Utf32Le,
+        Encoding::Utf32Be,
+    ];
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            Encoding::detect(b"\xef\xbb\xbfa"),
+            Some((Encoding::Utf8, 3))
+        );
+        assert_eq!(
+            Encoding::detect(b"\xff\xfea\x00"),
+            Some((Encoding::Utf16Le, 2))
+        );
+        assert_eq!(
+            Encoding::detect(b"\xfe\xff\x00a"),
+            Some((Encoding::Utf16Be, 2))
+        );
+        assert_eq!(
+            Encoding::detect(b"\xff\xfe\x00\x00a\x00\x00\x00"),
+            Some((Encoding::Utf32Le, 4))
+        );
+        assert_eq!(
+            Encoding::detect(b"\x00\x00\xfe\xff"),
+            Some((Encoding::Utf32Be, 4))
+        );
+        assert_eq!(Encoding::detect(b"plain"), None);
+        assert_eq!(Encoding::detect(b"\xff"), None);
+        assert_eq!(Encoding::detect(b""), None);
+    }
+
+    #[test]
+    fn test_decode_split_at_every_position() {
+        let text = "a\r\n中文\r\n😀 emoji\n";
+        for encoding in WIDE {
+            let data = encode(text, encoding);
+            for i in 0..=data.len() {
+                let mut decoder = Decoder::new(encoding);
+                let mut out = Vec::new();
+                decoder.decode(&data[..i], &mut out).unwrap();
+                decoder.decode(&data[i..], &mut out).unwrap();
+                decoder.finish().unwrap();
+                assert_eq!(out, text.as_bytes(), "{:?} 在 {} 处切分", encoding, i);
+            }
+            let mut decoder = Decoder::new(encoding);
+            let mut out = Vec::new();
+            for b in data.chunks(1) {
+                decoder.decode(b, &mut out).unwrap();
+            }
+            decoder.finish().unwrap();
+            assert_eq!(out, text.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let decode = |data: &[u8], encoding| {
+            let mut decoder = Decoder::new(encoding);
+            let mut out = Vec::new();
+            decoder
+                .decode(data, &mut out)
+                .and_then(|_| decoder.finish())
+                .map(|_| out)
+        };
+        // 孤立的低位代理项、高位代理项后跟普通字符、截断的代理对、奇数字节。
+        for data in [&b"\x00\xdc"[..], b"\x00\xd8a\x00", b"\x3d\xd8", b"a\x00b"] {
+            let err = decode(data, Encoding::Utf16Le).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", data);
+        }
+        assert!(decode(b"\x00\x00\x11\x00", Encoding::Utf32Le).is_err());
+        assert!(decode(b"\x00\xd8\x00\x00", Encoding::Utf32Le).is_err());
+        assert_eq!(decode(b"a\x00", Encoding::Utf16Le).unwrap(), b"a");
+    }
+}
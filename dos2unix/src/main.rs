@@ -1,7 +1,16 @@
 use std::env;
-use std::fs;
-use std::io::{self, Read, Write};
-use std::path::Path;
+use std::fs::{self, File, FileTimes, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+mod encoding;
+mod pool;
+mod walk;
+
+use encoding::{Decoder, Encoding, UTF8_BOM};
+use walk::{Entry, Filter};
 
 const NAME: &str = "dos2unix-rust";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -11,91 +20,1129 @@ fn print_help() {
         "{name} {version}\n\
 使用方法:\n\
   {name} [OPTIONS] [FILES...]\n\
+  {name} [OPTIONS] -n INFILE OUTFILE [INFILE OUTFILE ...] [-o FILES...]\n\
+  {name} [OPTIONS] -r DIR [--include GLOB] [--exclude GLOB]\n\
 \n\
 说明:\n\
-  将 CRLF 转换为 LF。若未提供 FILES 或 FILES 包含 '-'，则从标准输入读取并写到标准输出。\n\
+  将 CRLF 转换为 LF；使用 --to-dos 或以 unix2dos 为名调用时将单独的 LF 转换为 CRLF，\n\
+  以 mac2unix / unix2mac 为名调用时等同 --mac2unix / --unix2mac。\n\
+  以 BOM 开头的 UTF-16/UTF-32 输入会转换为 UTF-8 输出。\n\
+  若未提供 FILES 或 FILES 包含 '-'，则从标准输入读取并写到标准输出。\n\
 \n\
 选项:\n\
   -h, --help       显示帮助\n\
   -v, --version    显示版本\n\
-  -c, --check      检测模式，仅报告含 CRLF 的目标\n\
+  -c, --check      检测模式，仅报告需要转换的目标\n\
+  -f, --force      不检测二进制文件，强制转换\n\
+  -k, --keep-date  保留原文件的修改时间\n\
+  -n, --newfile INFILE OUTFILE ...\n\
+                   新文件模式：之后的参数两两成对，转换结果写入 OUTFILE，INFILE 不变\n\
+  -o, --oldfile FILES...\n\
+                   恢复就地转换模式（默认）\n\
+  -r, --recursive DIR\n\
+                   递归转换 DIR 下的文件（遵循 .gitignore，可重复），结束时输出汇总\n\
+      --include GLOB     递归时只处理匹配的文件（可重复）\n\
+      --exclude GLOB     递归时排除匹配的文件或目录（可重复）\n\
+  -i, --info       只统计不转换：每个文件输出 CRLF、LF、CR 数量、BOM、文本/二进制与末尾换行\n\
+      --json       --info 的结果按 JSON Lines 输出（隐含 --info）\n\
+  -j, --jobs N     用 N 个线程并行处理文件（0 为 CPU 数，默认 1），输出仍按参数顺序\n\
+      --follow-symlink   转换符号链接指向的文件\n\
+      --replace-symlink  用转换结果替换符号链接本身（原指向的文件不变）\n\
+      --skip-symlink     跳过符号链接（默认）\n\
+      --to-dos     反向转换：LF -> CRLF（已有的 CRLF 不会重复）\n\
+      --mac2unix   单独的 CR（经典 Mac）-> LF\n\
+      --unix2mac   单独的 LF -> CR\n\
+      --any2unix   CRLF、单独的 CR 都转换为 LF\n\
+      --keep-bom   保留 BOM（--to-dos、--unix2mac 的默认行为）\n\
+      --remove-bom 删除 BOM（其余方向的默认行为）\n\
+      --add-bom    输出一律以 UTF-8 BOM 开头\n\
   -q, --quiet      静默模式，减少输出\n\
 \n\
 示例:\n\
   {name} file.txt\n\
   type file.txt | {name} > out.txt\n\
-  {name} - file1.txt file2.txt\n",
+  {name} - file1.txt file2.txt\n\
+  {name} --to-dos notes.txt\n\
+  {name} -n in.txt out.txt\n\
+  {name} -r src --include '*.sh' --exclude 'vendor/**'\n\
+  {name} -j 8 -r .\n\
+  {name} --info --json -r src\n",
         name = NAME,
         version = VERSION
     );
     println!("{}", help);
 }
 
+/// 流式读写的块大小。
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 转换方向。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// CRLF -> LF（dos2unix）。
+    ToUnix,
+    /// 单独的 LF -> CRLF（unix2dos）；已有的 CRLF 保持不变。
+    ToDos,
+    /// 单独的 CR（经典 Mac OS 换行）-> LF（mac2unix）；CRLF 保持不变。
+    MacToUnix,
+    /// 单独的 LF -> CR（unix2mac）；CRLF 保持不变。
+    UnixToMac,
+    /// CRLF 与单独的 CR 都转换为 LF。
+    AnyToUnix,
+}
+
+impl Mode {
+    /// 按调用名选择默认方向：`unix2dos`、`mac2unix`、`unix2mac`，其余为 dos2unix。
+    fn for_program(argv0: &str) -> Mode {
+        let stem = Path::new(argv0)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        [
+            ("unix2dos", Mode::ToDos),
+            ("mac2unix", Mode::MacToUnix),
+            ("unix2mac", Mode::UnixToMac),
+        ]
+        .into_iter()
+        .find(|(name, _)| stem.starts_with(name))
+        .map_or(Mode::ToUnix, |(_, mode)| mode)
+    }
+
+    /// 需要转换的换行，用于提示信息。
+    fn source_desc(self) -> &'static str {
+        match self {
+            Mode::ToUnix => "CRLF",
+            Mode::ToDos | Mode::UnixToMac => "单独的 LF",
+            Mode::MacToUnix => "单独的 CR",
+            Mode::AnyToUnix => "CRLF 或单独的 CR",
+        }
+    }
+
+    /// 换行 `eol` 在输出中的写法。
+    fn output(self, eol: Eol) -> Eol {
+        match (self, eol) {
+            (Mode::ToUnix, Eol::Crlf) => Eol::Lf,
+            (Mode::ToDos, Eol::Lf) => Eol::Crlf,
+            (Mode::MacToUnix, Eol::Cr) => Eol::Lf,
+            (Mode::UnixToMac, Eol::Lf) => Eol::Cr,
+            (Mode::AnyToUnix, _) => Eol::Lf,
+            (_, eol) => eol,
+        }
+    }
+
+    /// 未指定 BOM 选项时的处理（同 GNU dos2unix）：转换为 DOS/Mac 换行时保留，其余删除。
+    fn default_bom(self) -> BomPolicy {
+        match self {
+            Mode::ToDos | Mode::UnixToMac => BomPolicy::Keep,
+            Mode::ToUnix | Mode::MacToUnix | Mode::AnyToUnix => BomPolicy::Remove,
+        }
+    }
+}
+
+/// 输出开头的 BOM 如何处理。输出总是 UTF-8，保留的 BOM 也写为 UTF-8 BOM。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BomPolicy {
+    /// 输入有 BOM 时输出也有。
+    Keep,
+    /// 输出不带 BOM。
+    Remove,
+    /// 输出总是带 BOM。
+    Add,
+}
+
+/// 输入中的换行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Eol {
+    Crlf,
+    Lf,
+    Cr,
+}
+
+impl Eol {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Eol::Crlf => b"\r\n",
+            Eol::Lf => b"\n",
+            Eol::Cr => b"\r",
+        }
+    }
+}
+
+/// 按类型统计的换行数量。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct EolCounts {
+    crlf: u64,
+    lf: u64,
+    cr: u64,
+}
+
+impl EolCounts {
+    fn add(&mut self, eol: Eol) {
+        match eol {
+            Eol::Crlf => self.crlf += 1,
+            Eol::Lf => self.lf += 1,
+            Eol::Cr => self.cr += 1,
+        }
+    }
+}
+
+/// 换行转换的流式状态机：块末尾的 `\r` 暂存到下一块，确定它是否属于 CRLF 后再输出，
+/// 因此输入可按任意位置切分，内存占用与输入大小无关。
+#[derive(Debug)]
+struct Converter {
+    mode: Mode,
+    pending_cr: bool,
+    /// 已改写的换行数量。
+    converted: u64,
+    /// 输入中各类换行的数量。
+    seen: EolCounts,
+}
+
+impl Converter {
+    fn new(mode: Mode) -> Self {
+        Converter {
+            mode,
+            pending_cr: false,
+            converted: 0,
+            seen: EolCounts::default(),
+        }
+    }
+
+    /// 转换一块输入，结果追加到 `out`。
+    fn feed(&mut self, mut input: &[u8], out: &mut Vec<u8>) {
+        while let Some((&b, rest)) = input.split_first() {
+            if std::mem::take(&mut self.pending_cr) {
+                if b == b'\n' {
+                    self.emit(Eol::Crlf, out);
+                    input = rest;
+                    continue;
+                }
+                self.emit(Eol::Cr, out);
+            }
+            // 非换行字节整段复制。
+            let run = input
+                .iter()
+                .position(|&b| b == b'\r' || b == b'\n')
+                .unwrap_or(input.len());
+            out.extend_from_slice(&input[..run]);
+            match input.get(run) {
+                Some(b'\r') => self.pending_cr = true,
+                Some(_) => self.emit(Eol::Lf, out),
+                None => {}
+            }
+            input = input.get(run + 1..).unwrap_or_default();
+        }
+    }
+
+    /// 输入结束：输出暂存的 `\r`。
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        if std::mem::take(&mut self.pending_cr) {
+            self.emit(Eol::Cr, out);
+        }
+    }
+
+    fn emit(&mut self, eol: Eol, out: &mut Vec<u8>) {
+        self.seen.add(eol);
+        let target = self.mode.output(eol);
+        if target != eol {
+            self.converted += 1;
+        }
+        out.extend_from_slice(target.bytes());
+    }
+}
+
+/// 整块将 CRLF 转换为 LF，单独的 CR 保持不变：即 [`Mode::ToUnix`] 下的 [`Converter`]。
+///
+/// 程序本身按块流式转换，这个整块接口保留给测试与需要一次性转换的调用方。
+#[cfg_attr(not(test), allow(dead_code))]
 fn convert_crlf_to_lf(input: &[u8]) -> Vec<u8> {
+    let mut conv = Converter::new(Mode::ToUnix);
     let mut out = Vec::with_capacity(input.len());
-    let mut i = 0;
-    while i < input.len() {
-        if input[i] == b'\r' && i + 1 < input.len() && input[i + 1] == b'\n' {
-            out.push(b'\n');
-            i += 2;
-        } else {
-            out.push(input[i]);
-            i += 1;
+    conv.feed(input, &mut out);
+    conv.finish(&mut out);
+    out
+}
+
+/// 一次转换的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Outcome {
+    /// 改写的换行数量。
+    eols: u64,
+    /// 输入为 UTF-16/UTF-32，已转为 UTF-8。
+    reencoded: bool,
+    /// 按 BOM 选项添加或删除了 BOM。
+    bom_changed: bool,
+}
+
+impl Outcome {
+    /// 输出与输入是否不同。
+    fn changed(self) -> bool {
+        self.eols > 0 || self.reencoded || self.bom_changed
+    }
+}
+
+/// 按 [`CHUNK_SIZE`] 分块将 `reader` 转换写入 `writer`。
+///
+/// 开头的 BOM 决定输入编码：UTF-16/UTF-32 先解码为 UTF-8 再转换换行，输出总是 UTF-8；
+/// 输出开头是否带 BOM 由 `bom` 决定。
+fn convert_stream<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    mode: Mode,
+    bom: BomPolicy,
+) -> io::Result<Outcome> {
+    convert_stream_with(reader, writer, mode, bom, |_| Ok(()))
+}
+
+/// 同 [`convert_stream`]，并在输出首次与输入不同之前调用一次 `on_change`：
+/// 此前写入 `writer` 的内容与输入的同长度前缀逐字节相同。
+fn convert_stream_with<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    mode: Mode,
+    bom: BomPolicy,
+    mut on_change: impl FnMut(&mut W) -> io::Result<()>,
+) -> io::Result<Outcome> {
+    let mut head = Vec::with_capacity(4);
+    (&mut reader).take(4).read_to_end(&mut head)?;
+    let (encoding, bom_len) = Encoding::detect(&head).unwrap_or((Encoding::Utf8, 0));
+    let had_bom = bom_len > 0;
+    let write_bom = match bom {
+        BomPolicy::Keep => had_bom,
+        BomPolicy::Remove => false,
+        BomPolicy::Add => true,
+    };
+    let reencoded = encoding != Encoding::Utf8;
+    let bom_changed = !reencoded && write_bom != had_bom;
+    let mut reader = head[bom_len..].chain(reader);
+    let mut changing = reencoded || bom_changed;
+    if changing {
+        on_change(&mut writer)?;
+    }
+    if write_bom {
+        writer.write_all(UTF8_BOM)?;
+    }
+
+    let mut decoder = reencoded.then(|| Decoder::new(encoding));
+    let mut conv = Converter::new(mode);
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut decoded = Vec::new();
+    let mut out = Vec::with_capacity(2 * CHUNK_SIZE);
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        out.clear();
+        match decoder.as_mut() {
+            Some(decoder) => {
+                decoded.clear();
+                decoder.decode(&buf[..n], &mut decoded)?;
+                conv.feed(&decoded, &mut out);
+            }
+            None => conv.feed(&buf[..n], &mut out),
         }
+        if !changing && conv.converted > 0 {
+            changing = true;
+            on_change(&mut writer)?;
+        }
+        writer.write_all(&out)?;
+    }
+    if let Some(decoder) = decoder.as_mut() {
+        decoder.finish()?;
+    }
+    out.clear();
+    conv.finish(&mut out);
+    if !changing && conv.converted > 0 {
+        on_change(&mut writer)?;
     }
+    writer.write_all(&out)?;
+    writer.flush()?;
+    Ok(Outcome {
+        eols: conv.converted,
+        reencoded,
+        bom_changed,
+    })
+}
+
+/// 文件的换行统计（`--info`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stats {
+    eols: EolCounts,
+    /// 开头的 BOM 所表示的编码。
+    bom: Option<Encoding>,
+    /// 按 [`looks_binary`] 判断。
+    binary: bool,
+    /// 最后一个字符是 LF 或 CR；空文件为否。
+    final_newline: bool,
+}
+
+impl Stats {
+    /// 一行统计结果：默认为类似 GNU `dos2unix -i` 的对齐列，`json` 时为一个 JSON 对象。
+    fn format_line(&self, name: &str, json: bool) -> String {
+        let bom = self.bom.map(Encoding::name);
+        if json {
+            return format!(
+                "{{\"path\":{},\"crlf\":{},\"lf\":{},\"cr\":{},\"bom\":{},\"binary\":{},\"final_newline\":{}}}",
+                json_string(name),
+                self.eols.crlf,
+                self.eols.lf,
+                self.eols.cr,
+                bom.map_or("null".to_string(), json_string),
+                self.binary,
+                self.final_newline
+            );
+        }
+        format!(
+            "{:>8}{:>8}{:>8}  {:<9} {:<7}{:<7}{}",
+            self.eols.crlf,
+            self.eols.lf,
+            self.eols.cr,
+            bom.unwrap_or("no_bom"),
+            if self.binary { "binary" } else { "text" },
+            if self.final_newline { "eol" } else { "no_eol" },
+            name
+        )
+    }
+}
+
+/// 带引号并转义的 JSON 字符串。
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
     out
 }
 
-fn process_stdin_stdout(quiet: bool, check_only: bool) -> io::Result<bool> {
-    let mut buf = Vec::new();
-    io::stdin().read_to_end(&mut buf)?;
-    let converted = convert_crlf_to_lf(&buf);
-    let has_crlf = converted != buf;
+/// 流式统计 `reader` 中的换行，不做转换：换行的划分与 [`Converter`] 相同。
+/// UTF-16/UTF-32 输入按解码后的字符统计，数据无效时报错。
+fn analyze<R: Read>(mut reader: R) -> io::Result<Stats> {
+    let head = read_head(&mut reader)?;
+    let detected = Encoding::detect(&head);
+    let bom_len = detected.map_or(0, |(_, len)| len);
+    let binary = looks_binary(&head);
+    let mut decoder = detected
+        .filter(|&(enc, _)| enc != Encoding::Utf8)
+        .map(|(enc, _)| Decoder::new(enc));
+    let mut reader = head[bom_len..].chain(reader);
+
+    let mut conv = Converter::new(Mode::ToUnix);
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut decoded = Vec::new();
+    let mut out = Vec::with_capacity(CHUNK_SIZE);
+    let mut last = None;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let text = match decoder.as_mut() {
+            Some(decoder) => {
+                decoded.clear();
+                decoder.decode(&buf[..n], &mut decoded)?;
+                &decoded[..]
+            }
+            None => &buf[..n],
+        };
+        if let Some(&b) = text.last() {
+            last = Some(b);
+        }
+        out.clear();
+        conv.feed(text, &mut out);
+    }
+    if let Some(decoder) = decoder.as_mut() {
+        decoder.finish()?;
+    }
+    conv.finish(&mut out);
+    Ok(Stats {
+        eols: conv.seen,
+        bom: detected.map(|(enc, _)| enc),
+        binary,
+        final_newline: matches!(last, Some(b'\n' | b'\r')),
+    })
+}
+
+/// 将 `reader`（`source` 的内容）转换后原子地写入 `target`，`meta` 为 `source` 的元数据。
+///
+/// 先写入 `target` 同目录下的临时文件并同步到磁盘，复制源文件的权限（`in_place` 时还有属主，
+/// `--keep-date` 时还有访问与修改时间），再重命名覆盖 `target`。任一步失败时删除临时文件，
+/// `target` 保持原样；重命名后 `target` 是新的 inode，原有的硬链接不再指向它。
+///
+/// 就地转换（`in_place`）时直到第一处改动才创建临时文件，并从 `source` 补写此前未改动的前缀；
+/// 无需转换的文件只读不写，只读目录中同样可以检查，`target` 连同修改时间不变。
+/// 新文件模式下即使无需转换也会写出 `target`。
+fn convert_atomic<R: Read>(
+    reader: R,
+    meta: &fs::Metadata,
+    source: &Path,
+    target: &Path,
+    opts: &Options,
+    in_place: bool,
+) -> io::Result<Outcome> {
+    let mut output = DeferredTemp {
+        source,
+        tmp: temp_path(target),
+        skipped: 0,
+        writer: None,
+    };
+    let result = (|| {
+        if !in_place {
+            output.start()?;
+        }
+        let outcome = convert_stream_with(reader, &mut output, opts.mode, opts.bom(), |out| {
+            out.start()
+        })?;
+        let Some(writer) = output.writer.take() else {
+            return Ok(outcome);
+        };
+        let file = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        copy_metadata(&file, meta, in_place, opts.keep_date)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&output.tmp, target)?;
+        Ok(outcome)
+    })();
+    if result.is_err() {
+        drop(output.writer.take());
+        let _ = fs::remove_file(&output.tmp);
+    }
+    result
+}
+
+/// [`convert_atomic`] 的输出：[`start`](Self::start) 之前只计数、不写盘。
+struct DeferredTemp<'a> {
+    source: &'a Path,
+    tmp: PathBuf,
+    /// 开始写入前略过的字节数，与源文件的同长度前缀相同。
+    skipped: u64,
+    writer: Option<BufWriter<File>>,
+}
+
+impl DeferredTemp<'_> {
+    /// 创建临时文件，并从源文件复制已略过的前缀；已开始时不做任何事。
+    fn start(&mut self) -> io::Result<()> {
+        if self.writer.is_some() {
+            return Ok(());
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&self.tmp)?;
+        let mut writer = BufWriter::with_capacity(CHUNK_SIZE, file);
+        let copied = io::copy(
+            &mut File::open(self.source)?.take(self.skipped),
+            &mut writer,
+        )?;
+        if copied != self.skipped {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "转换期间源文件被截断",
+            ));
+        }
+        self.writer = Some(writer);
+        Ok(())
+    }
+}
+
+impl Write for DeferredTemp<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.writer {
+            Some(writer) => writer.write(buf),
+            None => {
+                self.skipped += buf.len() as u64;
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 把原文件的权限（及可选的属主、时间戳）复制到新文件。
+///
+/// 属主无法保留时（如非 root 用户转换他人的文件）报错而不是悄悄改变属主。
+fn copy_metadata(
+    file: &File,
+    meta: &fs::Metadata,
+    keep_owner: bool,
+    keep_date: bool,
+) -> io::Result<()> {
+    #[cfg(not(unix))]
+    let _ = keep_owner;
+    #[cfg(unix)]
+    if keep_owner {
+        use std::os::unix::fs::{fchown, MetadataExt};
+        // 先改属主再设权限：chown 可能清除 setuid/setgid 位。
+        fchown(file, Some(meta.uid()), Some(meta.gid())).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("无法保留属主 {}:{}: {}", meta.uid(), meta.gid(), e),
+            )
+        })?;
+    }
+    file.set_permissions(meta.permissions())?;
+    if keep_date {
+        let times = FileTimes::new()
+            .set_accessed(meta.accessed()?)
+            .set_modified(meta.modified()?);
+        file.set_times(times)?;
+    }
+    Ok(())
+}
 
-    if check_only {
-        if has_crlf && !quiet {
+/// `path` 同目录下的临时文件路径；带进程内序号，并行转换同一文件时也不会冲突。
+fn temp_path(path: &Path) -> PathBuf {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.dos2unix-{}-{}.tmp",
+        name,
+        std::process::id(),
+        SEQ.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// 非文本控制字符占开头块的比例超过 1/`BINARY_RATIO` 时视为二进制文件。
+const BINARY_RATIO: usize = 10;
+
+/// 判断文件开头的数据是否为二进制：含 NUL，或非文本控制字符过多（同 GNU dos2unix 的思路）。
+/// 制表、换行、换页、退格、ESC 等常见于文本的控制字符不计；0x80 以上的字节按文本（如 UTF-8）处理。
+/// 以 UTF-16/UTF-32 BOM 开头的数据含大量 NUL，按文本处理。
+fn looks_binary(head: &[u8]) -> bool {
+    if matches!(Encoding::detect(head), Some((enc, _)) if enc != Encoding::Utf8) {
+        return false;
+    }
+    if head.contains(&0) {
+        return true;
+    }
+    let non_text = head
+        .iter()
+        .filter(|&&b| (b < 0x20 && !b"\t\n\r\x0b\x0c\x08\x1b".contains(&b)) || b == 0x7f)
+        .count();
+    non_text * BINARY_RATIO > head.len()
+}
+
+/// 读取开头的一块用于类型检测；返回读到的数据，调用方再与剩余部分拼接。
+fn read_head<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(CHUNK_SIZE);
+    reader.take(CHUNK_SIZE as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// 就地转换时对符号链接的处理（同 GNU dos2unix）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymlinkPolicy {
+    /// 跳过符号链接（默认）。
+    Skip,
+    /// 转换链接指向的文件，链接本身不变。
+    Follow,
+    /// 用转换结果替换链接本身，链接指向的文件不变。
+    Replace,
+}
+
+/// 命令行选项。
+#[derive(Debug, Clone, Copy)]
+struct Options {
+    mode: Mode,
+    quiet: bool,
+    check_only: bool,
+    /// 不检测二进制文件，一律转换。
+    force: bool,
+    /// 保留原文件的修改时间。
+    keep_date: bool,
+    symlinks: SymlinkPolicy,
+    /// 未指定时按 [`Mode::default_bom`]。
+    bom: Option<BomPolicy>,
+    /// 只统计不转换（`--info`）。
+    info: bool,
+    /// 统计结果输出为 JSON。
+    json: bool,
+}
+
+impl Options {
+    fn bom(&self) -> BomPolicy {
+        self.bom.unwrap_or(self.mode.default_bom())
+    }
+}
+
+fn process_stdin_stdout(opts: &Options) -> io::Result<bool> {
+    if opts.info {
+        println!(
+            "{}",
+            analyze(io::stdin().lock())?.format_line("-", opts.json)
+        );
+        return Ok(false);
+    }
+    let mut stdin = io::stdin().lock();
+    let head = read_head(&mut stdin)?;
+    let mut stdin = head.as_slice().chain(stdin);
+    if !opts.force && looks_binary(&head) {
+        // 作为过滤器时不能丢弃数据：原样输出。
+        if !opts.quiet {
+            eprintln!("跳过二进制数据: 标准输入（原样输出，使用 --force 强制转换）");
+        }
+        if !opts.check_only {
+            io::copy(&mut stdin, &mut io::stdout().lock())?;
+        }
+        return Ok(false);
+    }
+    if opts.check_only {
+        let needs = convert_stream(stdin, io::sink(), opts.mode, opts.bom())?.changed();
+        if needs && !opts.quiet {
             println!("-");
         }
-        return Ok(has_crlf);
+        return Ok(needs);
+    }
+
+    let changed = convert_stream(stdin, io::stdout().lock(), opts.mode, opts.bom())?.changed();
+    if !opts.quiet && !changed {
+        eprintln!("提示: 标准输入未发现{}。", opts.mode.source_desc());
+    }
+    Ok(changed)
+}
+
+/// 统计模式（`--info`）：输出一行统计，文件保持不变。
+fn process_info(path: &Path, opts: &Options, report: &mut Report) -> io::Result<FileStatus> {
+    let stats = analyze(File::open(path)?)?;
+    report.out(stats.format_line(&path.display().to_string(), opts.json));
+    Ok(FileStatus::Unchanged)
+}
+
+/// 一个任务的提示信息。并行处理时先缓存，轮到该任务时再按参数顺序打印。
+#[derive(Debug, Default)]
+struct Report {
+    /// `(是否写到标准错误, 内容)`。
+    lines: Vec<(bool, String)>,
+}
+
+impl Report {
+    fn out(&mut self, line: String) {
+        self.lines.push((false, line));
+    }
+
+    fn err(&mut self, line: String) {
+        self.lines.push((true, line));
+    }
+
+    fn print(self) {
+        for (to_stderr, line) in self.lines {
+            if to_stderr {
+                eprintln!("{}", line);
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+/// 打开源文件并读取开头一块用于二进制检测；开头与文件拼接后即为完整内容，整个文件只读一遍。
+fn open_source(path: &Path) -> io::Result<(Vec<u8>, File, fs::Metadata)> {
+    let mut file = File::open(path)?;
+    let meta = file.metadata()?;
+    let head = read_head(&mut file)?;
+    Ok((head, file, meta))
+}
+
+/// 未设置 `--force` 且文件开头 `head` 像二进制数据时提示并返回 `true`。
+fn skip_binary(path: &Path, head: &[u8], opts: &Options, report: &mut Report) -> bool {
+    if opts.force || !looks_binary(head) {
+        return false;
+    }
+    if !opts.quiet {
+        report.err(format!(
+            "跳过二进制文件: {}（使用 --force 强制转换）",
+            path.display()
+        ));
+    }
+    true
+}
+
+/// 判断两个路径是否指向同一文件；`b` 不存在时为否。
+///
+/// Unix 上比较设备号与 inode，硬链接与符号链接都能识别；其他平台比较规范化路径，只能识别符号链接。
+fn same_file(a: &Path, b: &Path) -> io::Result<bool> {
+    let b_meta = match fs::metadata(b) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let a_meta = fs::metadata(a)?;
+        Ok(a_meta.dev() == b_meta.dev() && a_meta.ino() == b_meta.ino())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = b_meta;
+        Ok(fs::canonicalize(a)? == fs::canonicalize(b)?)
+    }
+}
+
+/// 新文件模式（`-n`）：将 `input` 转换后原子地写入 `output`，原文件不变。
+///
+/// 即使无需转换也会写出 `output`；新文件沿用原文件的权限，属主为当前用户。
+fn process_new_file(
+    input: &Path,
+    output: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> io::Result<FileStatus> {
+    if same_file(input, output)? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "输入与输出为同一文件（请直接就地转换）",
+        ));
+    }
+    let (head, file, meta) = open_source(input)?;
+    if skip_binary(input, &head, opts, report) {
+        return Ok(FileStatus::Skipped);
+    }
+    let reader = head.as_slice().chain(file);
+    if opts.check_only {
+        let needs = convert_stream(reader, io::sink(), opts.mode, opts.bom())?.changed();
+        if needs && !opts.quiet {
+            report.out(input.display().to_string());
+        }
+        return Ok(FileStatus::from_changed(needs));
+    }
+
+    let needs = convert_atomic(reader, &meta, input, output, opts, false)?.changed();
+    if !opts.quiet {
+        let verb = if needs { "转换" } else { "未改变" };
+        report.out(format!(
+            "{}: {} -> {}",
+            verb,
+            input.display(),
+            output.display()
+        ));
     }
+    Ok(FileStatus::from_changed(needs))
+}
+
+/// 处理单个目标的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileStatus {
+    /// 已转换；检测模式下为需要转换。
+    Changed,
+    Unchanged,
+    /// 二进制文件或符号链接被跳过。
+    Skipped,
+}
+
+impl FileStatus {
+    fn from_changed(changed: bool) -> FileStatus {
+        if changed {
+            FileStatus::Changed
+        } else {
+            FileStatus::Unchanged
+        }
+    }
+}
+
+fn process_file(path: &Path, opts: &Options, report: &mut Report) -> io::Result<FileStatus> {
+    // 写入的目标：符号链接按策略决定改写链接指向的文件还是链接本身。
+    let target = if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        match opts.symlinks {
+            SymlinkPolicy::Skip => {
+                if !opts.quiet {
+                    report.err(format!(
+                        "跳过符号链接: {}（使用 --follow-symlink 或 --replace-symlink 转换）",
+                        path.display()
+                    ));
+                }
+                return Ok(FileStatus::Skipped);
+            }
+            SymlinkPolicy::Follow => fs::canonicalize(path)?,
+            SymlinkPolicy::Replace => path.to_path_buf(),
+        }
+    } else {
+        path.to_path_buf()
+    };
+    let (head, file, meta) = open_source(path)?;
+    if skip_binary(path, &head, opts, report) {
+        return Ok(FileStatus::Skipped);
+    }
+    let reader = head.as_slice().chain(file);
+
+    if opts.check_only {
+        let needs = convert_stream(reader, io::sink(), opts.mode, opts.bom())?.changed();
+        if needs && !opts.quiet {
+            report.out(path.display().to_string());
+        }
+        return Ok(FileStatus::from_changed(needs));
+    }
+
+    // 无需转换的文件不写回，保持修改时间不变。
+    let needs = convert_atomic(reader, &meta, path, &target, opts, true)?.changed();
+    if !opts.quiet {
+        let verb = if needs { "转换" } else { "未改变" };
+        report.out(format!("{}: {}", verb, path.display()));
+    }
+    Ok(FileStatus::from_changed(needs))
+}
+
+/// 处理结果的计数：每个 `-r` 目录各一份，另有一份覆盖全部目标、决定退出码。
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Summary {
+    changed: usize,
+    unchanged: usize,
+    skipped: usize,
+    errored: usize,
+}
+
+impl Summary {
+    fn record(&mut self, result: &io::Result<FileStatus>) {
+        match result {
+            Ok(FileStatus::Changed) => self.changed += 1,
+            Ok(FileStatus::Unchanged) => self.unchanged += 1,
+            Ok(FileStatus::Skipped) => self.skipped += 1,
+            Err(_) => self.errored += 1,
+        }
+    }
+
+    /// 递归模式结束时的汇总行，写到标准错误。
+    fn print(&self, dir: &Path, opts: &Options) {
+        if opts.quiet || opts.info {
+            return;
+        }
+        let changed = if opts.check_only {
+            "需要转换"
+        } else {
+            "转换"
+        };
+        eprintln!(
+            "汇总({}): {} {}，未改变 {}，跳过 {}，错误 {}",
+            dir.display(),
+            changed,
+            self.changed,
+            self.unchanged,
+            self.skipped,
+            self.errored
+        );
+    }
+}
+
+/// 展开后的处理任务：`-r` 目录展开为其中的每个文件。
+#[derive(Debug)]
+enum Task {
+    Stdio,
+    /// 就地转换；`in_dir` 表示来自 `-r`，计入该目录的汇总。
+    File {
+        path: PathBuf,
+        in_dir: bool,
+    },
+    NewFile(PathBuf, PathBuf),
+    /// 遍历 `-r` 目录时的错误。
+    WalkError(PathBuf, io::Error),
+    /// 一个 `-r` 目录的文件都已处理，输出汇总。
+    DirDone(PathBuf),
+}
+
+impl Task {
+    /// 按参数顺序展开目标；目录在处理前遍历完毕。
+    fn plan(targets: Vec<Target>, filter: &Filter) -> Vec<Task> {
+        let mut tasks = Vec::with_capacity(targets.len());
+        for target in targets {
+            match target {
+                Target::Stdio => tasks.push(Task::Stdio),
+                Target::File(path) => tasks.push(Task::File {
+                    path,
+                    in_dir: false,
+                }),
+                Target::NewFile(input, output) => tasks.push(Task::NewFile(input, output)),
+                Target::Dir(dir) => {
+                    tasks.extend(
+                        walk::walk(&dir, filter)
+                            .into_iter()
+                            .map(|entry| match entry {
+                                Entry::File(path) => Task::File { path, in_dir: true },
+                                Entry::Error(path, e) => Task::WalkError(path, e),
+                            }),
+                    );
+                    tasks.push(Task::DirDone(dir));
+                }
+            }
+        }
+        tasks
+    }
+
+    /// 错误信息中的目标名。
+    fn label(&self) -> String {
+        match self {
+            Task::Stdio => "标准流".to_string(),
+            Task::File { path, .. } | Task::WalkError(path, _) | Task::DirDone(path) => {
+                path.display().to_string()
+            }
+            Task::NewFile(input, output) => {
+                format!("{} -> {}", input.display(), output.display())
+            }
+        }
+    }
+
+    /// 在工作线程上执行；标准流与汇总须在主线程上按顺序处理，返回 `None`。
+    fn run(&self, opts: &Options) -> Option<(io::Result<FileStatus>, Report)> {
+        let mut report = Report::default();
+        let result = match self {
+            Task::File { path, .. } | Task::NewFile(path, _) if opts.info => {
+                process_info(path, opts, &mut report)
+            }
+            Task::File { path, .. } => process_file(path, opts, &mut report),
+            Task::NewFile(input, output) => process_new_file(input, output, opts, &mut report),
+            Task::WalkError(_, e) => Err(io::Error::new(e.kind(), e.to_string())),
+            Task::Stdio | Task::DirDone(_) => return None,
+        };
+        Some((result, report))
+    }
+}
+
+/// 用 `jobs` 个线程处理全部任务，输出与错误按参数顺序打印，返回覆盖全部目标的计数。
+fn run_tasks(tasks: &[Task], opts: &Options, jobs: usize) -> Summary {
+    let mut total = Summary::default();
+    let mut dir = Summary::default();
+    pool::run_ordered(
+        tasks,
+        jobs,
+        |task| task.run(opts),
+        |task, done| {
+            let result = match (task, done) {
+                (Task::DirDone(path), _) => {
+                    std::mem::take(&mut dir).print(path, opts);
+                    return;
+                }
+                (Task::Stdio, _) => process_stdin_stdout(opts).map(FileStatus::from_changed),
+                (_, Some((result, report))) => {
+                    report.print();
+                    result
+                }
+                (_, None) => unreachable!("只有标准流与汇总在主线程上执行"),
+            };
+            if let Err(e) = &result {
+                eprintln!("错误({}): {}", task.label(), e);
+            }
+            if matches!(task, Task::File { in_dir: true, .. } | Task::WalkError(..)) {
+                dir.record(&result);
+            }
+            total.record(&result);
+        },
+    );
+    total
+}
 
-    if !quiet && !has_crlf {
-        eprintln!("提示: 标准输入未发现 CRLF。");
+/// 命令行中的一个处理目标。
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// `-`：标准输入到标准输出。
+    Stdio,
+    /// 就地转换。
+    File(PathBuf),
+    /// `-n INFILE OUTFILE`。
+    NewFile(PathBuf, PathBuf),
+    /// `-r DIR`。
+    Dir(PathBuf),
+}
+
+impl Target {
+    fn from_arg(arg: String) -> Target {
+        if arg == "-" {
+            Target::Stdio
+        } else {
+            Target::File(PathBuf::from(arg))
+        }
     }
-    let mut stdout = io::stdout();
-    stdout.write_all(&converted)?;
-    stdout.flush()?;
-    Ok(has_crlf)
 }
 
-fn process_file(path: &Path, quiet: bool, check_only: bool) -> io::Result<bool> {
-    let data = fs::read(path)?;
-    let converted = convert_crlf_to_lf(&data);
-    let has_crlf = converted != data;
+/// 收集位置参数（同 GNU dos2unix）：`-n` 之后的参数两两组成 INFILE OUTFILE，
+/// 直到 `-o` 恢复就地转换。
+#[derive(Debug, Default)]
+struct Positionals {
+    targets: Vec<Target>,
+    newfile: bool,
+    /// 新文件模式下尚未配对的 INFILE。
+    pending: Option<PathBuf>,
+}
+
+impl Positionals {
+    fn push(&mut self, arg: String) {
+        if !self.newfile {
+            self.targets.push(Target::from_arg(arg));
+            return;
+        }
+        match self.pending.take() {
+            Some(input) => self.targets.push(Target::NewFile(input, arg.into())),
+            None => self.pending = Some(arg.into()),
+        }
+    }
 
-    if check_only {
-        if has_crlf && !quiet {
-            println!("{}", path.display());
+    /// 切换新文件模式（`-n`）与就地模式（`-o`）；上一组缺少 OUTFILE 时返回其 INFILE。
+    fn set_newfile(&mut self, newfile: bool) -> Result<(), PathBuf> {
+        match self.pending.take() {
+            Some(input) => Err(input),
+            None => {
+                self.newfile = newfile;
+                Ok(())
+            }
         }
-        return Ok(has_crlf);
     }
 
-    if has_crlf {
-        fs::write(path, &converted)?;
-        if !quiet {
-            println!("转换: {}", path.display());
+    /// 参数结束；最后一个 INFILE 缺少 OUTFILE 时返回它。
+    fn finish(self) -> Result<Vec<Target>, PathBuf> {
+        match self.pending {
+            Some(input) => Err(input),
+            None => Ok(self.targets),
         }
-    } else if !quiet {
-        println!("未改变: {}", path.display());
     }
-    Ok(has_crlf)
+}
+
+/// 新文件模式下参数个数为奇数：报错退出。
+fn missing_outfile(input: &Path) -> ! {
+    eprintln!("-n 之后的参数须成对给出：{} 缺少 OUTFILE", input.display());
+    eprintln!("使用 --help 查看用法。");
+    std::process::exit(2);
 }
 
 fn main() {
-    let mut args = env::args().skip(1);
-    let mut files: Vec<String> = Vec::new();
-    let mut quiet = false;
-    let mut check_only = false;
+    let mut args = env::args();
+    let program = args.next().unwrap_or_default();
+    let mut positionals = Positionals::default();
+    let mut filter = Filter::default();
+    let mut jobs = 1;
+    let mut opts = Options {
+        mode: Mode::for_program(&program),
+        quiet: false,
+        check_only: false,
+        force: false,
+        keep_date: false,
+        symlinks: SymlinkPolicy::Skip,
+        bom: None,
+        info: false,
+        json: false,
+    };
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
@@ -107,33 +1154,77 @@ fn main() {
                 println!("{} {}", NAME, VERSION);
                 return;
             }
-            "-q" | "--quiet" => quiet = true,
-            "-c" | "--check" => check_only = true,
+            "-q" | "--quiet" => opts.quiet = true,
+            "-c" | "--check" => opts.check_only = true,
+            "-f" | "--force" => opts.force = true,
+            "-k" | "--keep-date" => opts.keep_date = true,
+            "-i" | "--info" => opts.info = true,
+            "--json" => {
+                opts.info = true;
+                opts.json = true;
+            }
+            "--follow-symlink" => opts.symlinks = SymlinkPolicy::Follow,
+            "--replace-symlink" => opts.symlinks = SymlinkPolicy::Replace,
+            "--skip-symlink" => opts.symlinks = SymlinkPolicy::Skip,
+            "--to-dos" => opts.mode = Mode::ToDos,
+            "--mac2unix" => opts.mode = Mode::MacToUnix,
+            "--unix2mac" => opts.mode = Mode::UnixToMac,
+            "--any2unix" => opts.mode = Mode::AnyToUnix,
+            "--keep-bom" => opts.bom = Some(BomPolicy::Keep),
+            "--remove-bom" => opts.bom = Some(BomPolicy::Remove),
+            "--add-bom" => opts.bom = Some(BomPolicy::Add),
+            "-n" | "--newfile" | "-o" | "--oldfile" => {
+                let newfile = matches!(arg.as_str(), "-n" | "--newfile");
+                if let Err(input) = positionals.set_newfile(newfile) {
+                    missing_outfile(&input);
+                }
+            }
+            "-j" | "--jobs" => {
+                jobs = match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(0)) => thread::available_parallelism().map_or(1, |n| n.get()),
+                    Some(Ok(n)) => n,
+                    _ => {
+                        eprintln!("{} 需要一个非负整数参数", arg);
+                        eprintln!("使用 --help 查看用法。");
+                        std::process::exit(2);
+                    }
+                };
+            }
+            "-r" | "--recursive" | "--include" | "--exclude" => {
+                let Some(value) = args.next() else {
+                    eprintln!("{} 需要一个参数", arg);
+                    eprintln!("使用 --help 查看用法。");
+                    std::process::exit(2);
+                };
+                match arg.as_str() {
+                    "--include" => filter.include.push(value),
+                    "--exclude" => filter.exclude.push(value),
+                    _ => positionals.targets.push(Target::Dir(value.into())),
+                }
+            }
             "--" => {
-                files.extend(args);
+                args.by_ref().for_each(|arg| positionals.push(arg));
                 break;
             }
             _ => {
-                if arg == "-" {
-                    files.push(arg);
-                } else if arg.starts_with('-') {
+                if arg != "-" && arg.starts_with('-') {
                     eprintln!("未知选项: {}", arg);
                     eprintln!("使用 --help 查看用法。");
                     std::process::exit(2);
-                } else {
-                    files.push(arg);
                 }
+                positionals.push(arg);
             }
         }
     }
+    let targets = positionals
+        .finish()
+        .unwrap_or_else(|input| missing_outfile(&input));
 
-    if files.is_empty() {
-        match process_stdin_stdout(quiet, check_only) {
+    if targets.is_empty() {
+        match process_stdin_stdout(&opts) {
             Ok(has_crlf) => {
-                if has_crlf {
-                    if check_only {
-                        std::process::exit(2);
-                    }
+                if has_crlf && opts.check_only {
+                    std::process::exit(2);
                 }
             }
             Err(e) => {
@@ -144,50 +1235,33 @@ fn main() {
         return;
     }
 
-    let mut had_error = false;
-    let mut found_crlf = false;
+    let total = run_tasks(&Task::plan(targets, &filter), &opts, jobs);
 
-    for f in files {
-        if f == "-" {
-            match process_stdin_stdout(quiet, check_only) {
-                Ok(has_crlf) => {
-                    if has_crlf {
-                        found_crlf = true;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("错误(标准流): {}", e);
-                    had_error = true;
-                }
-            }
-        } else {
-            let p = std::path::Path::new(&f);
-            match process_file(p, quiet, check_only) {
-                Ok(has_crlf) => {
-                    if has_crlf {
-                        found_crlf = true;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("错误({}): {}", f, e);
-                    had_error = true;
-                }
-            }
-        }
-    }
-
-    if had_error {
+    if total.errored > 0 {
         std::process::exit(1);
     }
 
-    if check_only && found_crlf {
+    if opts.check_only && total.changed > 0 {
         std::process::exit(2);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::convert_crlf_to_lf;
+    use super::*;
+
+    /// 整块转换，供测试对照。
+    fn convert(input: &[u8], mode: Mode) -> Vec<u8> {
+        let mut conv = Converter::new(mode);
+        let mut out = Vec::with_capacity(input.len());
+        conv.feed(input, &mut out);
+        conv.finish(&mut out);
+        out
+    }
+
+    fn convert_lf_to_crlf(input: &[u8]) -> Vec<u8> {
+        convert(input, Mode::ToDos)
+    }
 
     #[test]
     fn test_empty() {
@@ -213,4 +1287,845 @@ mod tests {
     fn test_trailing_crlf() {
         assert_eq!(convert_crlf_to_lf(b"a\r\n"), b"a\n".to_vec());
     }
+
+    #[test]
+    fn test_to_dos_empty() {
+        assert_eq!(convert_lf_to_crlf(b""), b"".to_vec());
+    }
+
+    #[test]
+    fn test_to_dos_only_lf() {
+        assert_eq!(convert_lf_to_crlf(b"a\nb\n"), b"a\r\nb\r\n".to_vec());
+    }
+
+    #[test]
+    fn test_to_dos_only_crlf() {
+        assert_eq!(convert_lf_to_crlf(b"a\r\nb\r\n"), b"a\r\nb\r\n".to_vec());
+    }
+
+    #[test]
+    fn test_to_dos_mixed() {
+        assert_eq!(
+            convert_lf_to_crlf(b"a\n\r\nb\r\n"),
+            b"a\r\n\r\nb\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_to_dos_trailing_lf() {
+        assert_eq!(convert_lf_to_crlf(b"a\n"), b"a\r\n".to_vec());
+    }
+
+    #[test]
+    fn test_to_dos_roundtrip_idempotent() {
+        let input = b"x\r\ny\nz\r\r\n\n";
+        let dos = convert_lf_to_crlf(input);
+        assert_eq!(convert_lf_to_crlf(&dos), dos);
+        assert_eq!(convert_crlf_to_lf(&dos), convert_crlf_to_lf(input));
+    }
+
+    #[test]
+    fn test_mode_for_program() {
+        assert_eq!(Mode::for_program("unix2dos"), Mode::ToDos);
+        assert_eq!(Mode::for_program("/usr/bin/unix2dos"), Mode::ToDos);
+        assert_eq!(Mode::for_program("unix2dos.exe"), Mode::ToDos);
+        assert_eq!(Mode::for_program("mac2unix"), Mode::MacToUnix);
+        assert_eq!(Mode::for_program("unix2mac"), Mode::UnixToMac);
+        assert_eq!(Mode::for_program("dos2unix-rust"), Mode::ToUnix);
+        assert_eq!(Mode::for_program(""), Mode::ToUnix);
+    }
+
+    #[test]
+    fn test_mac2unix() {
+        let mac = |input: &[u8]| convert(input, Mode::MacToUnix);
+        assert_eq!(mac(b"a\rb\r"), b"a\nb\n".to_vec());
+        assert_eq!(mac(b"a\r\nb\n"), b"a\r\nb\n".to_vec());
+        assert_eq!(mac(b"\r\r\n"), b"\n\r\n".to_vec());
+        assert_eq!(mac(b"a\r"), b"a\n".to_vec());
+        assert_eq!(mac(b"\r\r"), b"\n\n".to_vec());
+    }
+
+    #[test]
+    fn test_unix2mac() {
+        let unix2mac = |input: &[u8]| convert(input, Mode::UnixToMac);
+        assert_eq!(unix2mac(b"a\nb\n"), b"a\rb\r".to_vec());
+        assert_eq!(unix2mac(b"a\r\nb\r"), b"a\r\nb\r".to_vec());
+        assert_eq!(unix2mac(b"\r\r\n\n"), b"\r\r\n\r".to_vec());
+        assert_eq!(convert(&unix2mac(b"x\ny\n"), Mode::MacToUnix), b"x\ny\n");
+    }
+
+    #[test]
+    fn test_any2unix() {
+        let any = |input: &[u8]| convert(input, Mode::AnyToUnix);
+        assert_eq!(any(b"a\r\nb\rc\nd"), b"a\nb\nc\nd".to_vec());
+        // CR 后紧跟 CRLF：先是单独的 CR，再是一个 CRLF，共两行。
+        assert_eq!(any(b"\r\r\n"), b"\n\n".to_vec());
+        assert_eq!(any(b"a\r\r\nb"), b"a\n\nb".to_vec());
+        assert_eq!(any(b"\n\r"), b"\n\n".to_vec());
+        assert_eq!(any(b"a\r"), b"a\n".to_vec());
+        assert_eq!(any(b"\r"), b"\n".to_vec());
+        assert_eq!(any(b"\r\r"), b"\n\n".to_vec());
+        assert_eq!(any(b""), b"".to_vec());
+    }
+
+    #[test]
+    fn test_cr_cr_lf_and_trailing_cr_per_mode() {
+        let cases: [(Mode, &[u8], &[u8]); 10] = [
+            (Mode::ToUnix, b"\r\r\n", b"\r\n"),
+            (Mode::ToUnix, b"a\r", b"a\r"),
+            (Mode::ToDos, b"\r\r\n", b"\r\r\n"),
+            (Mode::ToDos, b"a\r", b"a\r"),
+            (Mode::MacToUnix, b"\r\r\n", b"\n\r\n"),
+            (Mode::MacToUnix, b"a\r", b"a\n"),
+            (Mode::UnixToMac, b"\r\r\n", b"\r\r\n"),
+            (Mode::UnixToMac, b"a\r", b"a\r"),
+            (Mode::AnyToUnix, b"\r\r\n", b"\n\n"),
+            (Mode::AnyToUnix, b"a\r", b"a\n"),
+        ];
+        for (mode, input, expected) in cases {
+            assert_eq!(convert(input, mode), expected, "{:?}: {:?}", mode, input);
+        }
+    }
+
+    /// 逐字节的朴素实现，作为流式转换的对照；返回输出与改写的换行数。
+    fn reference(input: &[u8], mode: Mode) -> (Vec<u8>, u64) {
+        let mut out = Vec::new();
+        let mut converted = 0;
+        for (i, &b) in input.iter().enumerate() {
+            let after_cr = i > 0 && input[i - 1] == b'\r';
+            let before_lf = input.get(i + 1) == Some(&b'\n');
+            let replacement: Option<&[u8]> = match (mode, b) {
+                (Mode::ToUnix | Mode::AnyToUnix, b'\r') if before_lf => Some(b""),
+                (Mode::MacToUnix | Mode::AnyToUnix, b'\r') if !before_lf => Some(b"\n"),
+                (Mode::ToDos, b'\n') if !after_cr => Some(b"\r\n"),
+                (Mode::UnixToMac, b'\n') if !after_cr => Some(b"\r"),
+                _ => None,
+            };
+            match replacement {
+                Some(r) => {
+                    converted += 1;
+                    out.extend_from_slice(r);
+                }
+                None => out.push(b),
+            }
+        }
+        (out, converted)
+    }
+
+    const MODES: [Mode; 5] = [
+        Mode::ToUnix,
+        Mode::ToDos,
+        Mode::MacToUnix,
+        Mode::UnixToMac,
+        Mode::AnyToUnix,
+    ];
+
+    const SAMPLES: [&[u8]; 7] = [
+        b"a\r\nb\r\n",
+        b"\r\r\n\r",
+        b"\r\n\r\n\r\n",
+        b"x\ry\n\r",
+        b"\n\r\n\n\r\r",
+        b"\r",
+        b"",
+    ];
+
+    #[test]
+    fn test_split_at_every_position() {
+        for (mode, input) in MODES.iter().flat_map(|&m| SAMPLES.map(|s| (m, s))) {
+            let (expected, count) = reference(input, mode);
+            for i in 0..=input.len() {
+                let mut conv = Converter::new(mode);
+                let mut out = Vec::new();
+                conv.feed(&input[..i], &mut out);
+                conv.feed(&input[i..], &mut out);
+                conv.finish(&mut out);
+                assert_eq!(
+                    out, expected,
+                    "{:?}: 输入 {:?} 在 {} 处切分",
+                    mode, input, i
+                );
+                assert_eq!(conv.converted, count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_byte_at_a_time() {
+        for (mode, input) in MODES.iter().flat_map(|&m| SAMPLES.map(|s| (m, s))) {
+            let mut conv = Converter::new(mode);
+            let mut out = Vec::new();
+            for b in input.chunks(1) {
+                conv.feed(b, &mut out);
+            }
+            conv.finish(&mut out);
+            assert_eq!(out, reference(input, mode).0);
+        }
+    }
+
+    /// 每次只返回一个字节的读取器，模拟管道的短读。
+    struct OneByte<'a>(&'a [u8]);
+
+    impl Read for OneByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match (self.0.split_first(), buf.is_empty()) {
+                (Some((&b, rest)), false) => {
+                    buf[0] = b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert_stream_short_reads() {
+        for (mode, input) in MODES.iter().flat_map(|&m| SAMPLES.map(|s| (m, s))) {
+            let mut out = Vec::new();
+            let outcome = convert_stream(OneByte(input), &mut out, mode, BomPolicy::Keep).unwrap();
+            assert_eq!((out, outcome.eols), reference(input, mode));
+        }
+    }
+
+    #[test]
+    fn test_convert_atomic_across_chunks() {
+        // CRLF 恰好跨越读取块边界，且文件长度为多个块；三种换行都出现。
+        let mut data = vec![b'x'; CHUNK_SIZE - 1];
+        data.extend_from_slice(b"\r\n");
+        data.extend(b"line\r\nmac\runix\n".repeat(CHUNK_SIZE / 8));
+        data.push(b'\r');
+        let path = std::env::temp_dir().join(format!("dos2unix-inplace-{}", std::process::id()));
+        let convert_in_place = |mode| {
+            let mut opts = quiet_options();
+            opts.mode = mode;
+            opts.bom = Some(BomPolicy::Keep);
+            let meta = fs::metadata(&path).unwrap();
+            convert_atomic(File::open(&path).unwrap(), &meta, &path, &path, &opts, true).unwrap()
+        };
+        for mode in MODES {
+            fs::write(&path, &data).unwrap();
+            let outcome = convert_in_place(mode);
+            assert_eq!(
+                (fs::read(&path).unwrap(), outcome.eols),
+                reference(&data, mode)
+            );
+            let prefix = format!(".{}.dos2unix-", path.file_name().unwrap().to_string_lossy());
+            assert!(!fs::read_dir(path.parent().unwrap())
+                .unwrap()
+                .filter_map(Result::ok)
+                .any(|e| e.file_name().to_string_lossy().starts_with(&prefix)));
+        }
+        // 全部为单独 LF 的文件转换后长度翻倍。
+        fs::write(&path, b"\n".repeat(3 * CHUNK_SIZE)).unwrap();
+        convert_in_place(Mode::ToDos);
+        assert_eq!(fs::read(&path).unwrap(), b"\r\n".repeat(3 * CHUNK_SIZE));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_in_place_defers_temp_file() {
+        let dir = temp_dir("deferred");
+        let path = dir.join("a.txt");
+        let opts = quiet_options();
+        let convert = |data: &[u8], target: &Path| {
+            fs::write(&path, data).unwrap();
+            let meta = fs::metadata(&path).unwrap();
+            convert_atomic(
+                File::open(&path).unwrap(),
+                &meta,
+                &path,
+                target,
+                &opts,
+                true,
+            )
+        };
+
+        // 无需转换时不创建临时文件：目标目录不存在也不会出错。
+        let missing = dir.join("missing").join("a.txt");
+        let unchanged = b"lf only\n".repeat(CHUNK_SIZE / 4);
+        assert!(!convert(&unchanged, &missing).unwrap().changed());
+        assert!(convert(b"crlf\r\n", &missing).is_err());
+
+        // 唯一的改动在多个块之后：临时文件从原文件补写此前的前缀。
+        let mut late = unchanged.clone();
+        late.extend_from_slice(b"end\r\n");
+        let outcome = convert(&late, &path).unwrap();
+        assert_eq!(outcome.eols, 1);
+        let mut expected = unchanged;
+        expected.extend_from_slice(b"end\n");
+        assert_eq!(fs::read(&path).unwrap(), expected);
+
+        // 只删除 BOM 时从开头就写入。
+        convert(b"\xef\xbb\xbfa\n", &path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"a\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b""));
+        assert!(!looks_binary(b"plain text\r\nwith\ttabs\x0c\n"));
+        assert!(!looks_binary("中文文本\r\n".as_bytes()));
+        assert!(!looks_binary(b"\x1b[31mred\x1b[0m\r\n"));
+        assert!(looks_binary(b"text\0more"));
+        assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(looks_binary(b"\x7fELF\x02\x01\x01\x03\x04\x05\r\n"));
+        // 少量控制字符不影响判断。
+        let mut mostly_text = b"line\r\n".repeat(100);
+        mostly_text.extend_from_slice(b"\x01\x02");
+        assert!(!looks_binary(&mostly_text));
+    }
+
+    #[test]
+    fn test_binary_file_skipped_unless_forced() {
+        let path = std::env::temp_dir().join(format!("dos2unix-binary-{}", std::process::id()));
+        let data = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\r\n".to_vec();
+        fs::write(&path, &data).unwrap();
+        let mut opts = quiet_options();
+        assert_eq!(
+            process_file(&path, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Skipped
+        );
+        assert_eq!(fs::read(&path).unwrap(), data);
+
+        opts.force = true;
+        assert_eq!(
+            process_file(&path, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Changed
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"\x89PNG\n\x1a\n\0\0\0\rIHDR\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut data = b"\xff\xfe".to_vec();
+        data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        data
+    }
+
+    #[test]
+    fn test_utf16_to_utf8() {
+        let data = utf16le("第一行\r\nsecond\r\n");
+        let mut out = Vec::new();
+        let outcome =
+            convert_stream(OneByte(&data), &mut out, Mode::ToUnix, BomPolicy::Remove).unwrap();
+        assert_eq!(out, "第一行\nsecond\n".as_bytes());
+        assert_eq!(outcome.eols, 2);
+        assert!(outcome.reencoded);
+
+        // 换行已是目标格式也要重新编码；保留的 BOM 写为 UTF-8 BOM。
+        let mut be = b"\xfe\xff".to_vec();
+        be.extend("a\nb".encode_utf16().flat_map(u16::to_be_bytes));
+        let mut out = Vec::new();
+        let outcome =
+            convert_stream(be.as_slice(), &mut out, Mode::ToUnix, BomPolicy::Keep).unwrap();
+        assert_eq!(out, b"\xef\xbb\xbfa\nb");
+        assert!(outcome.changed());
+
+        let mut out = Vec::new();
+        let err = convert_stream(
+            &b"\xff\xfea\x00\x00\xdc"[..],
+            &mut out,
+            Mode::ToUnix,
+            BomPolicy::Keep,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_bom_policies() {
+        let cases: [(&[u8], BomPolicy, &[u8], bool); 6] = [
+            (
+                b"\xef\xbb\xbfa\n",
+                BomPolicy::Keep,
+                b"\xef\xbb\xbfa\n",
+                false,
+            ),
+            (b"\xef\xbb\xbfa\n", BomPolicy::Remove, b"a\n", true),
+            (
+                b"\xef\xbb\xbfa\n",
+                BomPolicy::Add,
+                b"\xef\xbb\xbfa\n",
+                false,
+            ),
+            (b"a\n", BomPolicy::Keep, b"a\n", false),
+            (b"a\n", BomPolicy::Remove, b"a\n", false),
+            (b"a\n", BomPolicy::Add, b"\xef\xbb\xbfa\n", true),
+        ];
+        for (input, bom, expected, changed) in cases {
+            let mut out = Vec::new();
+            let outcome = convert_stream(input, &mut out, Mode::ToUnix, bom).unwrap();
+            assert_eq!(out, expected, "{:?} {:?}", input, bom);
+            assert_eq!(outcome.changed(), changed, "{:?} {:?}", input, bom);
+        }
+        assert_eq!(Mode::ToUnix.default_bom(), BomPolicy::Remove);
+        assert_eq!(Mode::ToDos.default_bom(), BomPolicy::Keep);
+    }
+
+    #[test]
+    fn test_utf16_file_converted_in_place() {
+        let dir = temp_dir("utf16");
+        let path = dir.join("notes.txt");
+        let data = utf16le("x\r\n".repeat(CHUNK_SIZE).as_str());
+        assert!(looks_binary(&data[2..]));
+        assert!(!looks_binary(&data));
+        fs::write(&path, &data).unwrap();
+        assert_eq!(
+            process_file(&path, &quiet_options(), &mut Report::default()).unwrap(),
+            FileStatus::Changed
+        );
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            "x\n".repeat(CHUNK_SIZE).as_bytes()
+        );
+
+        // 无效数据报错，原文件不变。
+        let bad = b"\xff\xfea\x00\x00\xd8\r\x00\n\x00".to_vec();
+        fs::write(&path, &bad).unwrap();
+        assert!(process_file(&path, &quiet_options(), &mut Report::default()).is_err());
+        assert_eq!(fs::read(&path).unwrap(), bad);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn quiet_options() -> Options {
+        Options {
+            mode: Mode::ToUnix,
+            quiet: true,
+            check_only: false,
+            force: false,
+            keep_date: false,
+            symlinks: SymlinkPolicy::Skip,
+            bom: None,
+            info: false,
+            json: false,
+        }
+    }
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dos2unix-{}-{}", tag, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_keep_date_and_permissions() {
+        let dir = temp_dir("keep-date");
+        let path = dir.join("a.txt");
+        fs::write(&path, b"a\r\n").unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let mut opts = quiet_options();
+        opts.keep_date = true;
+        assert_eq!(
+            process_file(&path, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Changed
+        );
+        let meta = fs::metadata(&path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"a\n");
+        assert_eq!(meta.modified().unwrap(), old);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+        }
+
+        fs::write(&path, b"b\r\n").unwrap();
+        opts.keep_date = false;
+        let before = fs::metadata(&path).unwrap().modified().unwrap();
+        process_file(&path, &opts, &mut Report::default()).unwrap();
+        assert!(fs::metadata(&path).unwrap().modified().unwrap() >= before);
+        // 临时文件不残留。
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unchanged_file_not_rewritten() {
+        let dir = temp_dir("unchanged");
+        let path = dir.join("a.txt");
+        fs::write(&path, b"a\nb\n").unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let before = fs::metadata(&path).unwrap();
+
+        assert_eq!(
+            process_file(&path, &quiet_options(), &mut Report::default()).unwrap(),
+            FileStatus::Unchanged
+        );
+        let after = fs::metadata(&path).unwrap();
+        assert_eq!(after.modified().unwrap(), old);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(after.ino(), before.ino());
+        }
+        #[cfg(not(unix))]
+        let _ = before;
+        // 丢弃的临时文件不残留。
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policies() {
+        use std::os::unix::fs::symlink;
+
+        let dir = temp_dir("symlink");
+        let target = dir.join("target.txt");
+        let link = dir.join("link.txt");
+        symlink("target.txt", &link).unwrap();
+        let mut opts = quiet_options();
+
+        fs::write(&target, b"x\r\n").unwrap();
+        assert_eq!(
+            process_file(&link, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Skipped
+        );
+        assert_eq!(fs::read(&target).unwrap(), b"x\r\n");
+
+        opts.symlinks = SymlinkPolicy::Follow;
+        assert_eq!(
+            process_file(&link, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Changed
+        );
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read(&target).unwrap(), b"x\n");
+
+        fs::write(&target, b"y\r\n").unwrap();
+        opts.symlinks = SymlinkPolicy::Replace;
+        assert_eq!(
+            process_file(&link, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Changed
+        );
+        assert!(fs::symlink_metadata(&link).unwrap().is_file());
+        assert_eq!(fs::read(&link).unwrap(), b"y\n");
+        assert_eq!(fs::read(&target).unwrap(), b"y\r\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_new_file_mode() {
+        let dir = temp_dir("newfile");
+        let input = dir.join("in.txt");
+        let output = dir.join("sub-out.txt");
+        fs::write(&input, b"a\r\nb\r\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&input, fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        let opts = quiet_options();
+
+        assert_eq!(
+            process_new_file(&input, &output, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Changed
+        );
+        assert_eq!(fs::read(&input).unwrap(), b"a\r\nb\r\n");
+        assert_eq!(fs::read(&output).unwrap(), b"a\nb\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&output).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // 无需转换时仍写出副本，覆盖已有的输出。
+        let plain = dir.join("plain.txt");
+        fs::write(&plain, b"x\n").unwrap();
+        assert_eq!(
+            process_new_file(&plain, &output, &opts, &mut Report::default()).unwrap(),
+            FileStatus::Unchanged
+        );
+        assert_eq!(fs::read(&output).unwrap(), b"x\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn positionals(args: &[&str]) -> Result<Vec<Target>, PathBuf> {
+        let mut p = Positionals::default();
+        for &arg in args {
+            match arg {
+                "-n" | "-o" => p.set_newfile(arg == "-n")?,
+                _ => p.push(arg.to_string()),
+            }
+        }
+        p.finish()
+    }
+
+    #[test]
+    fn test_newfile_switches_to_pairs() {
+        let pair = |a: &str, b: &str| Target::NewFile(a.into(), b.into());
+        // 一个 -n 之后的全部参数两两成对，不会就地转换其中任何文件。
+        assert_eq!(
+            positionals(&["-n", "c.txt", "d.txt", "e.txt", "f.txt"]).unwrap(),
+            [pair("c.txt", "d.txt"), pair("e.txt", "f.txt")]
+        );
+        assert_eq!(
+            positionals(&["a.txt", "-n", "b.txt", "c.txt", "-o", "d.txt", "-"]).unwrap(),
+            [
+                Target::File("a.txt".into()),
+                pair("b.txt", "c.txt"),
+                Target::File("d.txt".into()),
+                Target::Stdio,
+            ]
+        );
+        assert_eq!(
+            positionals(&["-n", "c.txt", "d.txt", "e.txt"]).unwrap_err(),
+            PathBuf::from("e.txt")
+        );
+        assert_eq!(
+            positionals(&["-n", "c.txt", "-o", "d.txt"]).unwrap_err(),
+            PathBuf::from("c.txt")
+        );
+    }
+
+    #[test]
+    fn test_new_file_same_file_rejected() {
+        let dir = temp_dir("samefile");
+        let input = dir.join("in.txt");
+        fs::write(&input, b"a\r\n").unwrap();
+        let opts = quiet_options();
+
+        let alias = dir.join(".").join("in.txt");
+        let err = process_new_file(&input, &alias, &opts, &mut Report::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        #[cfg(unix)]
+        {
+            let hard = dir.join("hard.txt");
+            fs::hard_link(&input, &hard).unwrap();
+            assert!(process_new_file(&input, &hard, &opts, &mut Report::default()).is_err());
+
+            let soft = dir.join("soft.txt");
+            std::os::unix::fs::symlink(&input, &soft).unwrap();
+            assert!(process_new_file(&input, &soft, &opts, &mut Report::default()).is_err());
+            assert!(process_new_file(&soft, &input, &opts, &mut Report::default()).is_err());
+        }
+        assert_eq!(fs::read(&input).unwrap(), b"a\r\n");
+        assert!(!same_file(&input, &dir.join("missing.txt")).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recursive_summary() {
+        let dir = temp_dir("recursive");
+        fs::create_dir_all(dir.join("sub/vendor")).unwrap();
+        fs::write(dir.join(".gitignore"), "*.tmp\n").unwrap();
+        fs::write(dir.join("a.sh"), "a\r\n").unwrap();
+        fs::write(dir.join("b.sh"), "b\n").unwrap();
+        fs::write(dir.join("c.tmp"), "c\r\n").unwrap();
+        fs::write(dir.join("sub/d.sh"), b"\x89PNG\r\n\x1a\n\0\0\0\r").unwrap();
+        fs::write(dir.join("sub/vendor/e.sh"), "e\r\n").unwrap();
+        fs::write(dir.join("sub/f.txt"), "f\r\n").unwrap();
+        let filter = Filter {
+            include: vec!["*.sh".into()],
+            exclude: vec!["vendor".into()],
+        };
+
+        let mut opts = quiet_options();
+        opts.check_only = true;
+        let expected = Summary {
+            changed: 1,
+            unchanged: 1,
+            skipped: 1,
+            errored: 0,
+        };
+        let tasks = Task::plan(vec![Target::Dir(dir.clone())], &filter);
+        assert_eq!(run_tasks(&tasks, &opts, 1), expected);
+        assert_eq!(fs::read(dir.join("a.sh")).unwrap(), b"a\r\n");
+
+        opts.check_only = false;
+        assert_eq!(run_tasks(&tasks, &opts, 1), expected);
+        assert_eq!(fs::read(dir.join("a.sh")).unwrap(), b"a\n");
+        for untouched in ["c.tmp", "sub/vendor/e.sh", "sub/f.txt"] {
+            assert!(fs::read(dir.join(untouched)).unwrap().ends_with(b"\r\n"));
+        }
+
+        let tasks = Task::plan(vec![Target::Dir(dir.join("missing"))], &filter);
+        assert_eq!(run_tasks(&tasks, &opts, 1).errored, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let dir = temp_dir("parallel");
+        let mut targets = Vec::new();
+        for i in 0..200 {
+            let path = dir.join(format!("{:03}.txt", i));
+            let data: &[u8] = match i % 4 {
+                0 => b"a\r\nb\r\n",
+                1 => b"a\nb\n",
+                2 => b"\x7fELF\0\r\n",
+                _ => b"\xff\xfea\x00\r\x00\n\x00",
+            };
+            fs::write(&path, data).unwrap();
+            targets.push(Target::File(path));
+        }
+        // 输出到新文件、不存在的文件与目录。
+        targets.push(Target::NewFile(dir.join("001.txt"), dir.join("copy.txt")));
+        targets.push(Target::File(dir.join("missing.txt")));
+        targets.push(Target::Dir(dir.join("missing")));
+
+        let mut opts = quiet_options();
+        opts.check_only = true;
+        let tasks = Task::plan(targets, &Filter::default());
+        let sequential = run_tasks(&tasks, &opts, 1);
+        assert_eq!(run_tasks(&tasks, &opts, 8), sequential);
+
+        opts.check_only = false;
+        let converted = run_tasks(&tasks, &opts, 8);
+        assert_eq!(
+            converted,
+            Summary {
+                changed: 100,
+                unchanged: 51,
+                skipped: 50,
+                errored: 2,
+            }
+        );
+        for i in 0..200 {
+            let data = fs::read(dir.join(format!("{:03}.txt", i))).unwrap();
+            match i % 4 {
+                0 | 1 => assert_eq!(data, b"a\nb\n"),
+                2 => assert_eq!(data, b"\x7fELF\0\r\n"),
+                _ => assert_eq!(data, b"a\n"),
+            }
+        }
+        assert_eq!(fs::read(dir.join("copy.txt")).unwrap(), b"a\nb\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_analyze() {
+        let stats = analyze(OneByte(b"a\r\nb\nc\rd\r\r\n")).unwrap();
+        assert_eq!(
+            stats.eols,
+            EolCounts {
+                crlf: 2,
+                lf: 1,
+                cr: 2
+            }
+        );
+        assert_eq!(stats.bom, None);
+        assert!(!stats.binary && stats.final_newline);
+
+        assert!(!analyze(&b""[..]).unwrap().final_newline);
+        assert!(!analyze(&b"a\nb"[..]).unwrap().final_newline);
+        assert!(analyze(&b"a\r"[..]).unwrap().final_newline);
+        assert!(analyze(&b"\x7fELF\0\n"[..]).unwrap().binary);
+
+        // 按所有模式转换时统计的换行与 Converter 划分一致。
+        for input in SAMPLES {
+            let stats = analyze(input).unwrap();
+            let (_, any) = reference(input, Mode::AnyToUnix);
+            let (_, dos) = reference(input, Mode::ToUnix);
+            assert_eq!(stats.eols.crlf, dos);
+            assert_eq!(stats.eols.crlf + stats.eols.cr, any);
+        }
+
+        let data = utf16le("一\r\n二\n");
+        let stats = analyze(data.as_slice()).unwrap();
+        assert_eq!((stats.eols.crlf, stats.eols.lf), (1, 1));
+        assert_eq!(stats.bom, Some(Encoding::Utf16Le));
+        assert!(!stats.binary && stats.final_newline);
+        assert!(analyze(&b"\xff\xfea"[..]).is_err());
+    }
+
+    #[test]
+    fn test_stats_to_line() {
+        let stats = Stats {
+            eols: EolCounts {
+                crlf: 3,
+                lf: 0,
+                cr: 1,
+            },
+            bom: Some(Encoding::Utf8),
+            binary: false,
+            final_newline: false,
+        };
+        assert_eq!(
+            stats.format_line("a.txt", false),
+            "       3       0       1  UTF-8     text   no_eol a.txt"
+        );
+        assert_eq!(
+            stats.format_line("dir/\"q\"\\\t.txt", true),
+            r#"{"path":"dir/\"q\"\\\t.txt","crlf":3,"lf":0,"cr":1,"bom":"UTF-8","binary":false,"final_newline":false}"#
+        );
+        let stats = Stats {
+            bom: None,
+            binary: true,
+            ..stats
+        };
+        assert!(stats
+            .format_line("\u{1}", true)
+            .contains(r#""path":"\u0001","crlf":3"#));
+        assert!(stats
+            .format_line("x", true)
+            .contains(r#""bom":null,"binary":true"#));
+    }
+
+    #[test]
+    fn test_info_leaves_files_untouched() {
+        let dir = temp_dir("info");
+        let path = dir.join("a.txt");
+        fs::write(&path, b"a\r\n").unwrap();
+        let mut opts = quiet_options();
+        opts.info = true;
+        let tasks = Task::plan(
+            vec![
+                Target::File(path.clone()),
+                Target::NewFile(path.clone(), dir.join("b.txt")),
+            ],
+            &Filter::default(),
+        );
+        let total = run_tasks(&tasks, &opts, 2);
+        assert_eq!(total.unchanged, 2);
+        assert_eq!(fs::read(&path).unwrap(), b"a\r\n");
+        assert!(!dir.join("b.txt").exists());
+
+        let mut report = Report::default();
+        process_info(&path, &opts, &mut report).unwrap();
+        assert_eq!(
+            report.lines,
+            [(
+                false,
+                Stats {
+                    eols: EolCounts {
+                        crlf: 1,
+                        lf: 0,
+                        cr: 0
+                    },
+                    bom: None,
+                    binary: false,
+                    final_newline: true,
+                }
+                .format_line(&path.display().to_string(), false)
+            )]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,98 @@
+//! 固定大小的工作线程池：并行处理一组任务，结果按任务顺序交回调用线程。
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// 用 `jobs` 个工作线程对 `items` 逐个执行 `work`，并按 `items` 的顺序把结果交给 `done`。
+///
+/// `done` 在调用线程上执行：先完成的结果暂存，轮到它时再交出，因此输出顺序与串行处理相同。
+/// `jobs` 不大于 1 时直接在调用线程上串行处理。
+pub fn run_ordered<T, R, W, D>(items: &[T], jobs: usize, work: W, mut done: D)
+where
+    T: Sync,
+    R: Send,
+    W: Fn(&T) -> R + Sync,
+    D: FnMut(&T, R),
+{
+    if jobs <= 1 || items.len() <= 1 {
+        for item in items {
+            done(item, work(item));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            let tx = tx.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else { break };
+                if tx.send((i, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = BTreeMap::new();
+        let mut emitted = 0;
+        for (i, result) in rx {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&emitted) {
+                done(&items[emitted], result);
+                emitted += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_results_in_order() {
+        let items: Vec<u64> = (0..64).collect();
+        for jobs in [0, 1, 3, 8, 100] {
+            let mut seen = Vec::new();
+            run_ordered(
+                &items,
+                jobs,
+                |&i| {
+                    // 前面的任务更慢，迫使结果乱序到达。
+                    thread::sleep(Duration::from_micros((64 - i) * 50));
+                    i * 2
+                },
+                |&i, r| seen.push((i, r)),
+            );
+            let expected: Vec<_> = items.iter().map(|&i| (i, i * 2)).collect();
+            assert_eq!(seen, expected, "jobs = {}", jobs);
+        }
+    }
+
+    #[test]
+    fn test_work_runs_concurrently() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items = [(); 16];
+        run_ordered(
+            &items,
+            4,
+            |_| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+            },
+            |_, _| {},
+        );
+        assert!(peak.load(Ordering::SeqCst) > 1);
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+    }
+}
@@ -0,0 +1,398 @@
+//! 递归遍历目录：按 `--include`/`--exclude` 通配符与 `.gitignore` 规则筛选文件。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 递归处理时的文件筛选条件。
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// 非空时只处理匹配其中之一的文件。
+    pub include: Vec<String>,
+    /// 匹配的文件或目录（连同其内容）被排除。
+    pub exclude: Vec<String>,
+}
+
+/// 遍历得到的一项。
+#[derive(Debug)]
+pub enum Entry {
+    File(PathBuf),
+    /// 无法读取的目录或 `.gitignore`。
+    Error(PathBuf, io::Error),
+}
+
+/// 按文件名顺序深度优先遍历 `root`，返回筛选后的文件。
+///
+/// 不进入符号链接指向的目录，也不进入 `.git`；符号链接文件照常返回，由调用方按策略处理。
+pub fn walk(root: &Path, filter: &Filter) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut ignores = Vec::new();
+    visit(root, "", filter, &mut ignores, &mut entries);
+    entries
+}
+
+/// 一个 `.gitignore` 文件：`base` 为其所在目录相对遍历根的路径（根为空串）。
+#[derive(Debug)]
+struct IgnoreFile {
+    base: String,
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct IgnoreRule {
+    pattern: String,
+    negate: bool,
+    dir_only: bool,
+    /// 含 `/` 的规则相对 `.gitignore` 所在目录匹配，否则匹配任意层级的文件名。
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<IgnoreRule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.trim_start_matches('/').to_string();
+        (!pattern.is_empty()).then_some(IgnoreRule {
+            pattern,
+            negate,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, rel: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_match(&self.pattern, rel)
+        } else {
+            glob_match(&self.pattern, file_name(rel))
+        }
+    }
+}
+
+/// 按 git 的优先级判断：深层的 `.gitignore` 优先，同一文件中靠后的规则优先。
+fn ignored(ignores: &[IgnoreFile], rel: &str, is_dir: bool) -> bool {
+    for file in ignores.iter().rev() {
+        let local = if file.base.is_empty() {
+            rel
+        } else {
+            &rel[file.base.len() + 1..]
+        };
+        if let Some(rule) = file.rules.iter().rev().find(|r| r.matches(local, is_dir)) {
+            return !rule.negate;
+        }
+    }
+    false
+}
+
+/// 不含 `/` 的通配符匹配文件名，否则匹配相对遍历根的整个路径。
+fn filter_match(pattern: &str, rel: &str) -> bool {
+    if pattern.contains('/') {
+        glob_match(pattern.trim_start_matches('/'), rel)
+    } else {
+        glob_match(pattern, file_name(rel))
+    }
+}
+
+fn file_name(rel: &str) -> &str {
+    rel.rsplit('/').next().unwrap_or(rel)
+}
+
+fn visit(
+    dir: &Path,
+    rel: &str,
+    filter: &Filter,
+    ignores: &mut Vec<IgnoreFile>,
+    entries: &mut Vec<Entry>,
+) {
+    let mut children = match fs::read_dir(dir).and_then(|it| it.collect::<io::Result<Vec<_>>>()) {
+        Ok(children) => children,
+        Err(e) => {
+            entries.push(Entry::Error(dir.to_path_buf(), e));
+            return;
+        }
+    };
+    children.sort_by_key(|c| c.file_name());
+
+    let gitignore = dir.join(".gitignore");
+    let pushed = match fs::read_to_string(&gitignore) {
+        Ok(text) => {
+            ignores.push(IgnoreFile {
+                base: rel.to_string(),
+                rules: text.lines().filter_map(IgnoreRule::parse).collect(),
+            });
+            true
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+        Err(e) => {
+            entries.push(Entry::Error(gitignore, e));
+            false
+        }
+    };
+
+    for child in children {
+        let name = child.file_name();
+        let name = name.to_string_lossy();
+        let child_rel = if rel.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", rel, name)
+        };
+        let path = child.path();
+        let is_dir = match child.file_type() {
+            Ok(t) => t.is_dir(),
+            Err(e) => {
+                entries.push(Entry::Error(path, e));
+                continue;
+            }
+        };
+        if (is_dir && name == ".git")
+            || filter.exclude.iter().any(|p| filter_match(p, &child_rel))
+            || ignored(ignores, &child_rel, is_dir)
+        {
+            continue;
+        }
+        if is_dir {
+            visit(&path, &child_rel, filter, ignores, entries);
+        } else if filter.include.is_empty()
+            || filter.include.iter().any(|p| filter_match(p, &child_rel))
+        {
+            entries.push(Entry::File(path));
+        }
+    }
+
+    if pushed {
+        ignores.pop();
+    }
+}
+
+/// 以 `/` 分隔的通配符匹配：`*`、`?`、`[...]` 不跨越 `/`，`**` 匹配任意层目录（含零层），
+/// 因此 `vendor/**` 也匹配 `vendor` 本身。
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| match_segments(rest, &path[i..])),
+        Some((p, rest)) => path.split_first().is_some_and(|(s, path_rest)| {
+            match_segment(
+                &p.chars().collect::<Vec<_>>(),
+                &s.chars().collect::<Vec<_>>(),
+            ) && match_segments(rest, path_rest)
+        }),
+    }
+}
+
+/// 单个路径段的匹配；`*` 回溯到最近一次出现处重试。
+fn match_segment(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            Some('[') => {
+                if let Some((matched, len)) = match_class(&pattern[p..], name[n]) {
+                    if matched {
+                        p += len;
+                        n += 1;
+                        continue;
+                    }
+                } else if name[n] == '[' {
+                    // 未闭合的 `[` 按字面匹配。
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+            }
+            Some('\\') if pattern.get(p + 1) == Some(&name[n]) => {
+                p += 2;
+                n += 1;
+                continue;
+            }
+            Some(&c) if c == name[n] => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match star {
+            Some((sp, sn)) => {
+                p = sp + 1;
+                n = sn + 1;
+                star = Some((sp, sn + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 匹配以 `[` 开头的字符类，返回是否匹配与字符类的长度；未闭合时返回 `None`。
+fn match_class(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negate = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negate {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let lo = *pattern.get(i)?;
+        if lo == ']' && !first {
+            return Some((matched != negate, i + 1));
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&hi| hi != ']') {
+            let hi = pattern[i + 2];
+            matched |= lo <= c && c <= hi;
+            i += 3;
+        } else {
+            matched |= lo == c;
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.sh", "build.sh"));
+        assert!(!glob_match("*.sh", "build.shx"));
+        assert!(!glob_match("*.sh", "dir/build.sh"));
+        assert!(glob_match("*", ".hidden"));
+        assert!(glob_match("a*b*c", "aXXbYYbc"));
+        assert!(glob_match("?.txt", "中.txt"));
+        assert!(glob_match("[a-c]x[!0-9]", "bxz"));
+        assert!(!glob_match("[a-c]x[!0-9]", "bx1"));
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("\\*", "*"));
+        assert!(!glob_match("\\*", "a"));
+        assert!(glob_match("vendor/**", "vendor"));
+        assert!(glob_match("vendor/**", "vendor/a/b.c"));
+        assert!(!glob_match("vendor/**", "src/vendor/a"));
+        assert!(glob_match("**/vendor/**", "src/vendor/a"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/a/b/c.rs"));
+        assert!(!glob_match("src/*.rs", "src/a/c.rs"));
+    }
+
+    #[test]
+    fn test_ignore_rule_parse() {
+        assert_eq!(IgnoreRule::parse("# comment"), None);
+        assert_eq!(IgnoreRule::parse("   "), None);
+        let rule = IgnoreRule::parse("!/build/").unwrap();
+        assert_eq!(rule.pattern, "build");
+        assert!(rule.negate && rule.dir_only && rule.anchored);
+        let rule = IgnoreRule::parse("*.log").unwrap();
+        assert!(!rule.negate && !rule.dir_only && !rule.anchored);
+        assert!(rule.matches("a/b/x.log", false));
+        assert!(IgnoreRule::parse("doc/*.md")
+            .unwrap()
+            .matches("doc/a.md", false));
+        assert!(!IgnoreRule::parse("doc/*.md")
+            .unwrap()
+            .matches("x/doc/a.md", false));
+        assert!(!IgnoreRule::parse("out/").unwrap().matches("out", false));
+    }
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dos2unix-{}-{}", tag, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rel_files(root: &Path, filter: &Filter) -> Vec<String> {
+        walk(root, filter)
+            .into_iter()
+            .map(|e| match e {
+                Entry::File(p) => p
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                Entry::Error(p, e) => panic!("{}: {}", p.display(), e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_filters_and_gitignore() {
+        let root = temp_dir("walk");
+        for f in [
+            "a.sh",
+            "b.txt",
+            "debug.log",
+            "keep.log",
+            "src/c.sh",
+            "src/gen/d.sh",
+            "src/gen/e.sh",
+            "vendor/lib/f.sh",
+            "target/g.sh",
+            ".git/hooks/h.sh",
+        ] {
+            let path = root.join(f);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x\r\n").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "*.log\n!keep.log\n/target/\n").unwrap();
+        fs::write(root.join("src/gen/.gitignore"), "*\n!e.sh\n").unwrap();
+
+        assert_eq!(
+            rel_files(&root, &Filter::default()),
+            [
+                ".gitignore",
+                "a.sh",
+                "b.txt",
+                "keep.log",
+                "src/c.sh",
+                "src/gen/e.sh",
+                "vendor/lib/f.sh"
+            ]
+        );
+        let filter = Filter {
+            include: vec!["*.sh".into()],
+            exclude: vec!["vendor/**".into()],
+        };
+        assert_eq!(
+            rel_files(&root, &filter),
+            ["a.sh", "src/c.sh", "src/gen/e.sh"]
+        );
+        let filter = Filter {
+            include: vec!["src/**".into()],
+            exclude: vec!["gen".into()],
+        };
+        assert_eq!(rel_files(&root, &filter), ["src/c.sh"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+//! 压缩吞吐基准：比较 `io::copy` 默认 8 KiB 缓冲、更大缓冲与 mmap 输入路径。
+//!
+//! 运行：`cargo bench --bench throughput`
+
+use std::fs;
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rgzip::{compress_path_with_options, CompressOptions};
+
+const INPUT_LEN: usize = 32 * 1024 * 1024;
+
+/// 生成可压缩但非全零的输入（近似日志/CSV）。
+fn sample_input() -> Vec<u8> {
+    let mut data = Vec::with_capacity(INPUT_LEN);
+    let mut i = 0u64;
+    while data.len() < INPUT_LEN {
+        let line = format!(
+            "{},user{},GET /api/v1/items/{} 200 {}\n",
+            i,
+            i % 1000,
+            i.wrapping_mul(2654435761) % 100_000,
+            i % 5000
+        );
+        data.extend_from_slice(line.as_bytes());
+        i += 1;
+    }
+    data.truncate(INPUT_LEN);
+    data
+}
+
+fn bench_compress(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("rgzip-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.csv");
+    fs::write(&input, sample_input()).unwrap();
+    let output: PathBuf = dir.join("output.csv.gz");
+
+    let mut group = c.benchmark_group("compress_path_level1");
+    group.throughput(Throughput::Bytes(INPUT_LEN as u64));
+    group.sample_size(10);
+
+    let cases = [
+        ("buf-8K", 8 * 1024, false),
+        ("buf-128K", 128 * 1024, false),
+        ("buf-1M", 1024 * 1024, false),
+        ("mmap", 128 * 1024, true),
+    ];
+    for (name, buffer_size, mmap) in cases {
+        let opts = CompressOptions {
+            level: 1,
+            buffer_size,
+            mmap,
+            ..Default::default()
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(name), &opts, |b, opts| {
+            b.iter(|| compress_path_with_options(&input, &output, opts).unwrap())
+        });
+    }
+    group.finish();
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+criterion_group!(benches, bench_compress);
+criterion_main!(benches);
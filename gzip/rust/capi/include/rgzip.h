@@ -0,0 +1,194 @@
+#ifndef RGZIP_H
+#define RGZIP_H
+
+/* 由 cbindgen 根据 capi/src/lib.rs 生成，请勿手动修改。 */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * 默认压缩级别（与 zlib 的 `Z_DEFAULT_COMPRESSION` 一样以 -1 表示）。
+ */
+#define RGZIP_DEFAULT_LEVEL -1
+
+/**
+ * 返回码。
+ */
+typedef enum RgzipStatus {
+  RGZIP_STATUS_OK = 0,
+  /**
+   * 参数无效：空指针、路径不是合法字符串、压缩级别超出范围，或句柄已结束。
+   */
+  RGZIP_STATUS_INVALID_ARGUMENT = 1,
+  /**
+   * 输入文件不存在。
+   */
+  RGZIP_STATUS_NOT_FOUND = 2,
+  RGZIP_STATUS_PERMISSION_DENIED = 3,
+  /**
+   * 其他 I/O 错误。
+   */
+  RGZIP_STATUS_IO = 4,
+  /**
+   * 数据不是合法的 gzip 流或校验失败。
+   */
+  RGZIP_STATUS_CORRUPT = 5,
+  /**
+   * gzip 流不完整。
+   */
+  RGZIP_STATUS_TRUNCATED = 6,
+  /**
+   * 内部错误（Rust panic）。
+   */
+  RGZIP_STATUS_PANIC = 7,
+} RgzipStatus;
+
+/**
+ * 流式解压句柄：写入 gzip 数据（可含多个成员），读出解压数据。
+ *
+ * 解码使用 rgzip 的 [`GzPushDecoder`]，与同步、异步接口一致：截断报告为 `TRUNCATED`，
+ * 数据损坏报告为 `CORRUPT`。写入只暂存压缩数据，解压在读取时进行且每次不超过调用方的缓冲区，
+ * 因此内存占用与压缩比无关。
+ */
+typedef struct RgzipDecoder RgzipDecoder;
+
+/**
+ * 流式压缩句柄：写入未压缩数据，读出 gzip 数据。
+ */
+typedef struct RgzipEncoder RgzipEncoder;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * 当前线程上一次失败的错误信息（UTF-8，以 NUL 结尾）；上一次调用成功时为 `NULL`。
+ *
+ * 返回的指针在同一线程下一次调用本库函数之前有效，调用方不得释放。
+ */
+const char *rgzip_last_error(void);
+
+/**
+ * 库版本号（以 NUL 结尾的静态字符串）。
+ */
+const char *rgzip_version(void);
+
+/**
+ * 将 `input` 压缩为 gzip 文件 `output`（已存在时覆盖），`level` 为 0-9 或 `RGZIP_DEFAULT_LEVEL`。
+ *
+ * # Safety
+ * `input` 与 `output` 必须是以 NUL 结尾的字符串。
+ */
+enum RgzipStatus rgzip_compress_file(const char *input,
+                                     const char *output,
+                                     int level);
+
+/**
+ * 将 gzip 文件 `input`（可含多个成员）解压为 `output`；失败时删除不完整的输出。
+ *
+ * # Safety
+ * `input` 与 `output` 必须是以 NUL 结尾的字符串。
+ */
+enum RgzipStatus rgzip_decompress_file(const char *input,
+                                       const char *output);
+
+/**
+ * 创建流式压缩句柄，gzip 头与命令行工具压缩标准输入时相同；`level` 无效时返回 `NULL`。用 [`rgzip_encoder_free`] 释放。
+ */
+struct RgzipEncoder *rgzip_encoder_new(int level);
+
+/**
+ * 写入 `len` 字节未压缩数据；产生的压缩数据暂存在句柄中，应及时用 [`rgzip_encoder_read`] 取出。
+ *
+ * # Safety
+ * `encoder` 为有效句柄；`data` 指向至少 `len` 字节（`len` 为 0 时可为 `NULL`）。
+ */
+enum RgzipStatus rgzip_encoder_write(struct RgzipEncoder *encoder,
+                                     const uint8_t *data,
+                                     size_t len);
+
+/**
+ * 结束压缩流，写出剩余数据与 gzip 尾部；之后只能读取，重复调用无副作用。
+ *
+ * # Safety
+ * `encoder` 为有效句柄。
+ */
+enum RgzipStatus rgzip_encoder_finish(struct RgzipEncoder *encoder);
+
+/**
+ * 取出至多 `cap` 字节已产生的压缩数据，返回实际字节数；没有待读数据时返回 0。
+ *
+ * # Safety
+ * `encoder` 为有效句柄；`out` 指向至少 `cap` 字节的可写内存。
+ */
+size_t rgzip_encoder_read(struct RgzipEncoder *encoder,
+                          uint8_t *out,
+                          size_t cap);
+
+/**
+ * 当前可读取的压缩数据字节数。
+ *
+ * # Safety
+ * `encoder` 为有效句柄。
+ */
+size_t rgzip_encoder_available(struct RgzipEncoder *encoder);
+
+/**
+ * 释放压缩句柄；`NULL` 时不做任何事。
+ *
+ * # Safety
+ * `encoder` 为 `NULL` 或由 [`rgzip_encoder_new`] 返回且尚未释放。
+ */
+void rgzip_encoder_free(struct RgzipEncoder *encoder);
+
+/**
+ * 创建流式解压句柄。用 [`rgzip_decoder_free`] 释放。
+ */
+struct RgzipDecoder *rgzip_decoder_new(void);
+
+/**
+ * 写入 `len` 字节 gzip 数据。数据只暂存在句柄中，由 [`rgzip_decoder_read`] 解压取出。
+ *
+ * # Safety
+ * `decoder` 为有效句柄；`data` 指向至少 `len` 字节（`len` 为 0 时可为 `NULL`）。
+ */
+enum RgzipStatus rgzip_decoder_write(struct RgzipDecoder *decoder,
+                                     const uint8_t *data,
+                                     size_t len);
+
+/**
+ * 输入结束；之后继续用 [`rgzip_decoder_read`] 读出剩余数据，重复调用无副作用。
+ *
+ * # Safety
+ * `decoder` 为有效句柄。
+ */
+enum RgzipStatus rgzip_decoder_finish(struct RgzipDecoder *decoder);
+
+/**
+ * 解压至多 `cap` 字节到 `out`，实际字节数写入 `*written`；每次调用解压的数据不超过 `cap`。
+ *
+ * 返回 `RGZIP_STATUS_OK` 且 `*written` 为 0 时：调用 [`rgzip_decoder_finish`] 之前表示需要写入更多数据，
+ * 之后表示流已结束。数据损坏返回 `RGZIP_STATUS_CORRUPT`；输入结束时 gzip 流在成员中途截断返回
+ * `RGZIP_STATUS_TRUNCATED`。
+ *
+ * # Safety
+ * `decoder` 为有效句柄；`out` 指向至少 `cap` 字节的可写内存；`written` 指向可写的 `size_t`。
+ */
+enum RgzipStatus rgzip_decoder_read(struct RgzipDecoder *decoder,
+                                    uint8_t *out,
+                                    size_t cap,
+                                    size_t *written);
+
+/**
+ * 释放解压句柄；`NULL` 时不做任何事。
+ *
+ * # Safety
+ * `decoder` 为 `NULL` 或由 [`rgzip_decoder_new`] 返回且尚未释放。
+ */
+void rgzip_decoder_free(struct RgzipDecoder *decoder);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RGZIP_H */
@@ -0,0 +1,652 @@
+//! rgzip 的 C ABI：文件级压缩/解压与基于句柄的流式接口。
+//!
+//! 所有函数返回 [`RgzipStatus`]（或在失败时返回 `NULL`），失败原因可由 [`rgzip_last_error`]
+//! 取得；Rust 侧的 panic 不会越过 FFI 边界，而是报告为 `RGZIP_STATUS_PANIC`。
+//! 头文件 `include/rgzip.h` 由 cbindgen 生成。
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::io::{self, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+use std::slice;
+
+use flate2::write::GzEncoder;
+use rgzip::{compress_path_with_options, decompress_path_with_options, stream_encoder};
+use rgzip::{CompressOptions, DecompressOptions, GzPushDecoder, MAX_LEVEL};
+
+/// 默认压缩级别（与 zlib 的 `Z_DEFAULT_COMPRESSION` 一样以 -1 表示）。
+pub const RGZIP_DEFAULT_LEVEL: c_int = -1;
+
+/// 返回码。
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgzipStatus {
+    Ok = 0,
+    /// 参数无效：空指针、路径不是合法字符串、压缩级别超出范围，或句柄已结束。
+    InvalidArgument = 1,
+    /// 输入文件不存在。
+    NotFound = 2,
+    PermissionDenied = 3,
+    /// 其他 I/O 错误。
+    Io = 4,
+    /// 数据不是合法的 gzip 流或校验失败。
+    Corrupt = 5,
+    /// gzip 流不完整。
+    Truncated = 6,
+    /// 内部错误（Rust panic）。
+    Panic = 7,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(msg: String) {
+    let msg = CString::new(msg.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+fn fail(status: RgzipStatus, msg: impl Into<String>) -> RgzipStatus {
+    set_last_error(msg.into());
+    status
+}
+
+fn status_of(e: &io::Error) -> RgzipStatus {
+    match e.kind() {
+        io::ErrorKind::NotFound => RgzipStatus::NotFound,
+        io::ErrorKind::PermissionDenied => RgzipStatus::PermissionDenied,
+        // flate2 以 InvalidInput 报告损坏的 gzip 数据。
+        io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput => RgzipStatus::Corrupt,
+        io::ErrorKind::UnexpectedEof => RgzipStatus::Truncated,
+        _ => RgzipStatus::Io,
+    }
+}
+
+fn from_io(e: io::Error) -> RgzipStatus {
+    fail(status_of(&e), e.to_string())
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let detail = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    format!("rgzip 内部错误: {}", detail)
+}
+
+/// 执行 `f` 并捕获 panic，成功时清除上次的错误信息。
+fn guard(f: impl FnOnce() -> RgzipStatus) -> RgzipStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(RgzipStatus::Ok) => {
+            LAST_ERROR.with(|e| *e.borrow_mut() = None);
+            RgzipStatus::Ok
+        }
+        Ok(status) => status,
+        Err(payload) => fail(RgzipStatus::Panic, panic_message(&*payload)),
+    }
+}
+
+fn level_of(level: c_int) -> Result<u32, RgzipStatus> {
+    match level {
+        RGZIP_DEFAULT_LEVEL => Ok(CompressOptions::default().level),
+        0.. if level as u32 <= MAX_LEVEL => Ok(level as u32),
+        _ => Err(fail(
+            RgzipStatus::InvalidArgument,
+            format!("压缩级别应为 0-{} 或 -1: {}", MAX_LEVEL, level),
+        )),
+    }
+}
+
+/// # Safety
+/// `ptr` 为空或指向以 NUL 结尾的字符串。
+unsafe fn path_arg(ptr: *const c_char, what: &str) -> Result<PathBuf, RgzipStatus> {
+    if ptr.is_null() {
+        return Err(fail(
+            RgzipStatus::InvalidArgument,
+            format!("{} 为空指针", what),
+        ));
+    }
+    let bytes = CStr::from_ptr(ptr).to_bytes();
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(PathBuf::from(std::ffi::OsStr::from_bytes(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        std::str::from_utf8(bytes).map(PathBuf::from).map_err(|_| {
+            fail(
+                RgzipStatus::InvalidArgument,
+                format!("{} 不是合法的 UTF-8", what),
+            )
+        })
+    }
+}
+
+/// # Safety
+/// `data` 为空时 `len` 必须为 0，否则指向至少 `len` 字节的可读内存。
+unsafe fn bytes_arg<'a>(data: *const u8, len: usize) -> Result<&'a [u8], RgzipStatus> {
+    match (data.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(fail(RgzipStatus::InvalidArgument, "data 为空指针")),
+        (false, _) => Ok(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// 当前线程上一次失败的错误信息（UTF-8，以 NUL 结尾）；上一次调用成功时为 `NULL`。
+///
+/// 返回的指针在同一线程下一次调用本库函数之前有效，调用方不得释放。
+#[no_mangle]
+pub extern "C" fn rgzip_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// 库版本号（以 NUL 结尾的静态字符串）。
+#[no_mangle]
+pub extern "C" fn rgzip_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// 将 `input` 压缩为 gzip 文件 `output`（已存在时覆盖），`level` 为 0-9 或 `RGZIP_DEFAULT_LEVEL`。
+///
+/// # Safety
+/// `input` 与 `output` 必须是以 NUL 结尾的字符串。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_compress_file(
+    input: *const c_char,
+    output: *const c_char,
+    level: c_int,
+) -> RgzipStatus {
+    guard(|| {
+        let run = || -> Result<(), RgzipStatus> {
+            let input = path_arg(input, "input")?;
+            let output = path_arg(output, "output")?;
+            let opts = CompressOptions::with_level(level_of(level)?);
+            compress_path_with_options(&input, &output, &opts).map_err(from_io)
+        };
+        run().err().unwrap_or(RgzipStatus::Ok)
+    })
+}
+
+/// 将 gzip 文件 `input`（可含多个成员）解压为 `output`；失败时删除不完整的输出。
+///
+/// # Safety
+/// `input` 与 `output` 必须是以 NUL 结尾的字符串。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_decompress_file(
+    input: *const c_char,
+    output: *const c_char,
+) -> RgzipStatus {
+    guard(|| {
+        let run = || -> Result<(), RgzipStatus> {
+            let input = path_arg(input, "input")?;
+            let output = path_arg(output, "output")?;
+            decompress_path_with_options(&input, &output, &DecompressOptions::default())
+                .map_err(from_io)
+        };
+        run().err().unwrap_or(RgzipStatus::Ok)
+    })
+}
+
+/// 流式压缩句柄：写入未压缩数据，读出 gzip 数据。
+pub struct RgzipEncoder {
+    inner: GzEncoder<Vec<u8>>,
+    finished: bool,
+}
+
+/// 流式解压句柄：写入 gzip 数据（可含多个成员），读出解压数据。
+///
+/// 解码使用 rgzip 的 [`GzPushDecoder`]，与同步、异步接口一致：截断报告为 `TRUNCATED`，
+/// 数据损坏报告为 `CORRUPT`。写入只暂存压缩数据，解压在读取时进行且每次不超过调用方的缓冲区，
+/// 因此内存占用与压缩比无关。
+pub struct RgzipDecoder {
+    inner: GzPushDecoder,
+    finished: bool,
+}
+
+/// 两种句柄的共同操作。
+trait Handle {
+    fn finished(&mut self) -> &mut bool;
+    fn write_input(&mut self, data: &[u8]) -> io::Result<()>;
+    fn finish_input(&mut self) -> io::Result<()>;
+}
+
+impl Handle for RgzipEncoder {
+    fn finished(&mut self) -> &mut bool {
+        &mut self.finished
+    }
+
+    fn write_input(&mut self, data: &[u8]) -> io::Result<()> {
+        self.inner.write_all(data)
+    }
+
+    fn finish_input(&mut self) -> io::Result<()> {
+        self.inner.try_finish()
+    }
+}
+
+impl Handle for RgzipDecoder {
+    fn finished(&mut self) -> &mut bool {
+        &mut self.finished
+    }
+
+    fn write_input(&mut self, data: &[u8]) -> io::Result<()> {
+        self.inner.push(data);
+        Ok(())
+    }
+
+    fn finish_input(&mut self) -> io::Result<()> {
+        self.inner.finish();
+        Ok(())
+    }
+}
+
+/// # Safety
+/// `handle` 为空或是由对应的 `*_new` 返回且尚未释放的句柄。
+unsafe fn handle_arg<'a, H>(handle: *mut H) -> Result<&'a mut H, RgzipStatus> {
+    handle
+        .as_mut()
+        .ok_or_else(|| fail(RgzipStatus::InvalidArgument, "句柄为空指针"))
+}
+
+unsafe fn handle_write<H: Handle>(handle: *mut H, data: *const u8, len: usize) -> RgzipStatus {
+    guard(|| {
+        let run = || -> Result<(), RgzipStatus> {
+            let handle = handle_arg(handle)?;
+            let data = bytes_arg(data, len)?;
+            if *handle.finished() {
+                return Err(fail(RgzipStatus::InvalidArgument, "句柄已结束，不能再写入"));
+            }
+            handle.write_input(data).map_err(from_io)
+        };
+        run().err().unwrap_or(RgzipStatus::Ok)
+    })
+}
+
+unsafe fn handle_finish<H: Handle>(handle: *mut H) -> RgzipStatus {
+    guard(|| {
+        let run = || -> Result<(), RgzipStatus> {
+            let handle = handle_arg(handle)?;
+            if !*handle.finished() {
+                handle.finish_input().map_err(from_io)?;
+                *handle.finished() = true;
+            }
+            Ok(())
+        };
+        run().err().unwrap_or(RgzipStatus::Ok)
+    })
+}
+
+/// 创建流式压缩句柄，gzip 头与命令行工具压缩标准输入时相同；`level` 无效时返回 `NULL`。用 [`rgzip_encoder_free`] 释放。
+#[no_mangle]
+pub extern "C" fn rgzip_encoder_new(level: c_int) -> *mut RgzipEncoder {
+    match level_of(level) {
+        Ok(level) => {
+            let opts = CompressOptions::with_level(level);
+            match stream_encoder(Vec::new(), &opts) {
+                Ok(inner) => Box::into_raw(Box::new(RgzipEncoder {
+                    inner,
+                    finished: false,
+                })),
+                Err(e) => {
+                    from_io(e);
+                    ptr::null_mut()
+                }
+            }
+        }
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// 写入 `len` 字节未压缩数据；产生的压缩数据暂存在句柄中，应及时用 [`rgzip_encoder_read`] 取出。
+///
+/// # Safety
+/// `encoder` 为有效句柄；`data` 指向至少 `len` 字节（`len` 为 0 时可为 `NULL`）。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_encoder_write(
+    encoder: *mut RgzipEncoder,
+    data: *const u8,
+    len: usize,
+) -> RgzipStatus {
+    handle_write(encoder, data, len)
+}
+
+/// 结束压缩流，写出剩余数据与 gzip 尾部；之后只能读取，重复调用无副作用。
+///
+/// # Safety
+/// `encoder` 为有效句柄。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_encoder_finish(encoder: *mut RgzipEncoder) -> RgzipStatus {
+    handle_finish(encoder)
+}
+
+/// 取出至多 `cap` 字节已产生的压缩数据，返回实际字节数；没有待读数据时返回 0。
+///
+/// # Safety
+/// `encoder` 为有效句柄；`out` 指向至少 `cap` 字节的可写内存。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_encoder_read(
+    encoder: *mut RgzipEncoder,
+    out: *mut u8,
+    cap: usize,
+) -> usize {
+    let Ok(encoder) = handle_arg(encoder) else {
+        return 0;
+    };
+    if out.is_null() {
+        return 0;
+    }
+    let pending = encoder.inner.get_mut();
+    let n = pending.len().min(cap);
+    ptr::copy_nonoverlapping(pending.as_ptr(), out, n);
+    pending.drain(..n);
+    n
+}
+
+/// 当前可读取的压缩数据字节数。
+///
+/// # Safety
+/// `encoder` 为有效句柄。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_encoder_available(encoder: *mut RgzipEncoder) -> usize {
+    handle_arg(encoder).map_or(0, |e| e.inner.get_ref().len())
+}
+
+/// 释放压缩句柄；`NULL` 时不做任何事。
+///
+/// # Safety
+/// `encoder` 为 `NULL` 或由 [`rgzip_encoder_new`] 返回且尚未释放。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_encoder_free(encoder: *mut RgzipEncoder) {
+    if !encoder.is_null() {
+        drop(Box::from_raw(encoder));
+    }
+}
+
+/// 创建流式解压句柄。用 [`rgzip_decoder_free`] 释放。
+#[no_mangle]
+pub extern "C" fn rgzip_decoder_new() -> *mut RgzipDecoder {
+    Box::into_raw(Box::new(RgzipDecoder {
+        inner: GzPushDecoder::new(),
+        finished: false,
+    }))
+}
+
+/// 写入 `len` 字节 gzip 数据。数据只暂存在句柄中，由 [`rgzip_decoder_read`] 解压取出。
+///
+/// # Safety
+/// `decoder` 为有效句柄；`data` 指向至少 `len` 字节（`len` 为 0 时可为 `NULL`）。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_decoder_write(
+    decoder: *mut RgzipDecoder,
+    data: *const u8,
+    len: usize,
+) -> RgzipStatus {
+    handle_write(decoder, data, len)
+}
+
+/// 输入结束；之后继续用 [`rgzip_decoder_read`] 读出剩余数据，重复调用无副作用。
+///
+/// # Safety
+/// `decoder` 为有效句柄。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_decoder_finish(decoder: *mut RgzipDecoder) -> RgzipStatus {
+    handle_finish(decoder)
+}
+
+/// 解压至多 `cap` 字节到 `out`，实际字节数写入 `*written`；每次调用解压的数据不超过 `cap`。
+///
+/// 返回 `RGZIP_STATUS_OK` 且 `*written` 为 0 时：调用 [`rgzip_decoder_finish`] 之前表示需要写入更多数据，
+/// 之后表示流已结束。数据损坏返回 `RGZIP_STATUS_CORRUPT`；输入结束时 gzip 流在成员中途截断返回
+/// `RGZIP_STATUS_TRUNCATED`。
+///
+/// # Safety
+/// `decoder` 为有效句柄；`out` 指向至少 `cap` 字节的可写内存；`written` 指向可写的 `size_t`。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_decoder_read(
+    decoder: *mut RgzipDecoder,
+    out: *mut u8,
+    cap: usize,
+    written: *mut usize,
+) -> RgzipStatus {
+    guard(|| {
+        let run = || -> Result<(), RgzipStatus> {
+            let decoder = handle_arg(decoder)?;
+            let written = written
+                .as_mut()
+                .ok_or_else(|| fail(RgzipStatus::InvalidArgument, "written 为空指针"))?;
+            *written = 0;
+            if cap == 0 {
+                return Ok(());
+            }
+            if out.is_null() {
+                return Err(fail(RgzipStatus::InvalidArgument, "out 为空指针"));
+            }
+            let out = slice::from_raw_parts_mut(out, cap);
+            *written = decoder.inner.read(out).map_err(from_io)?.unwrap_or(0);
+            Ok(())
+        };
+        run().err().unwrap_or(RgzipStatus::Ok)
+    })
+}
+
+/// 释放解压句柄；`NULL` 时不做任何事。
+///
+/// # Safety
+/// `decoder` 为 `NULL` 或由 [`rgzip_decoder_new`] 返回且尚未释放。
+#[no_mangle]
+pub unsafe extern "C" fn rgzip_decoder_free(decoder: *mut RgzipDecoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(data: &[u8], level: c_int) -> Vec<u8> {
+        unsafe {
+            let enc = rgzip_encoder_new(level);
+            assert!(!enc.is_null());
+            let mut out = vec![0u8; 1024];
+            let mut gz = Vec::new();
+            for chunk in data.chunks(777) {
+                assert_eq!(
+                    rgzip_encoder_write(enc, chunk.as_ptr(), chunk.len()),
+                    RgzipStatus::Ok
+                );
+            }
+            assert_eq!(rgzip_encoder_finish(enc), RgzipStatus::Ok);
+            loop {
+                let n = rgzip_encoder_read(enc, out.as_mut_ptr(), out.len());
+                if n == 0 {
+                    break;
+                }
+                gz.extend_from_slice(&out[..n]);
+            }
+            rgzip_encoder_free(enc);
+            gz
+        }
+    }
+
+    /// 读出当前输入能解压出的全部数据（每次至多 1000 字节），返回最后一次读取的状态。
+    unsafe fn drain(dec: *mut RgzipDecoder, plain: &mut Vec<u8>) -> RgzipStatus {
+        let mut buf = [0u8; 1000];
+        loop {
+            let mut n = 0;
+            let status = rgzip_decoder_read(dec, buf.as_mut_ptr(), buf.len(), &mut n);
+            if status != RgzipStatus::Ok || n == 0 {
+                return status;
+            }
+            plain.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    /// 按 `step` 字节逐段写入解码器，返回解压结果与结束时的状态。
+    fn decode(gz: &[u8], step: usize) -> (Vec<u8>, RgzipStatus) {
+        unsafe {
+            let dec = rgzip_decoder_new();
+            let mut plain = Vec::new();
+            for chunk in gz.chunks(step) {
+                assert_eq!(
+                    rgzip_decoder_write(dec, chunk.as_ptr(), chunk.len()),
+                    RgzipStatus::Ok
+                );
+                let status = drain(dec, &mut plain);
+                if status != RgzipStatus::Ok {
+                    rgzip_decoder_free(dec);
+                    return (plain, status);
+                }
+            }
+            assert_eq!(rgzip_decoder_finish(dec), RgzipStatus::Ok);
+            let status = drain(dec, &mut plain);
+            rgzip_decoder_free(dec);
+            (plain, status)
+        }
+    }
+
+    #[test]
+    fn test_stream_roundtrip_any_chunking() {
+        let data: Vec<u8> = (0..50_000u32)
+            .flat_map(|i| (i % 251).to_le_bytes())
+            .collect();
+        let mut gz = encode(&data, RGZIP_DEFAULT_LEVEL);
+        // 第二个成员在前一个成员结束后才到达。
+        let tail = encode(b"second member", 1);
+        gz.extend_from_slice(&tail);
+        let mut expected = data.clone();
+        expected.extend_from_slice(b"second member");
+        for step in [1, 7, 4096, gz.len()] {
+            assert_eq!(decode(&gz, step), (expected.clone(), RgzipStatus::Ok));
+        }
+        assert_eq!(decode(&encode(b"", 9), 1), (Vec::new(), RgzipStatus::Ok));
+    }
+
+    #[test]
+    fn test_encoder_header_matches_library() {
+        let data = b"same bytes as the CLI".repeat(50);
+        let expected = rgzip::compress_reader_to_writer(&data[..], Vec::new(), 9).unwrap();
+        assert_eq!(encode(&data, 9), expected);
+    }
+
+    #[test]
+    fn test_decoder_inflates_at_most_cap_per_read() {
+        // 16 MiB 的零压缩后只有十几 KiB；一次写入全部输入不应解压出全部数据。
+        let size = 16 << 20;
+        let gz = encode(&vec![0u8; size], 9);
+        unsafe {
+            let dec = rgzip_decoder_new();
+            assert_eq!(
+                rgzip_decoder_write(dec, gz.as_ptr(), gz.len()),
+                RgzipStatus::Ok
+            );
+            let mut buf = [0u8; 4096];
+            let mut n = 0;
+            assert_eq!(
+                rgzip_decoder_read(dec, buf.as_mut_ptr(), buf.len(), &mut n),
+                RgzipStatus::Ok
+            );
+            assert_eq!(n, buf.len());
+            assert_eq!(
+                rgzip_decoder_read(dec, buf.as_mut_ptr(), 0, &mut n),
+                RgzipStatus::Ok
+            );
+            assert_eq!(n, 0);
+            assert_eq!(
+                rgzip_decoder_read(dec, buf.as_mut_ptr(), buf.len(), ptr::null_mut()),
+                RgzipStatus::InvalidArgument
+            );
+            assert_eq!(rgzip_decoder_finish(dec), RgzipStatus::Ok);
+            let mut plain = Vec::new();
+            assert_eq!(drain(dec, &mut plain), RgzipStatus::Ok);
+            assert_eq!(plain.len() + 4096, size);
+            rgzip_decoder_free(dec);
+        }
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let gz = encode(b"hello hello hello", 6);
+        for cut in [1, 5, gz.len() / 2, gz.len() - 1] {
+            assert_eq!(
+                decode(&gz[..cut], 3).1,
+                RgzipStatus::Truncated,
+                "cut {}",
+                cut
+            );
+        }
+        let mut bad = gz.clone();
+        let crc = bad.len() - 8;
+        bad[crc] ^= 0xff;
+        assert_eq!(decode(&bad, 4).1, RgzipStatus::Corrupt);
+        assert_eq!(decode(b"not gzip at all", 4).1, RgzipStatus::Corrupt);
+        let msg = unsafe { CStr::from_ptr(rgzip_last_error()) };
+        assert!(!msg.to_bytes().is_empty());
+
+        assert!(rgzip_encoder_new(10).is_null());
+        assert!(rgzip_encoder_new(-2).is_null());
+        unsafe {
+            let enc = rgzip_encoder_new(0);
+            assert_eq!(rgzip_encoder_finish(enc), RgzipStatus::Ok);
+            assert!(rgzip_last_error().is_null());
+            let data = b"late";
+            assert_eq!(
+                rgzip_encoder_write(enc, data.as_ptr(), data.len()),
+                RgzipStatus::InvalidArgument
+            );
+            assert_eq!(
+                rgzip_encoder_write(enc, ptr::null(), 1),
+                RgzipStatus::InvalidArgument
+            );
+            rgzip_encoder_free(enc);
+            assert_eq!(
+                rgzip_decoder_finish(ptr::null_mut()),
+                RgzipStatus::InvalidArgument
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_functions() {
+        let dir = std::env::temp_dir().join(format!("rgzip-capi-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| CString::new(dir.join(name).to_str().unwrap()).unwrap();
+        std::fs::write(dir.join("a.txt"), b"file level api\n".repeat(100)).unwrap();
+        unsafe {
+            let (a, gz, out) = (path("a.txt"), path("a.txt.gz"), path("a.out"));
+            assert_eq!(
+                rgzip_compress_file(a.as_ptr(), gz.as_ptr(), 9),
+                RgzipStatus::Ok
+            );
+            assert_eq!(
+                rgzip_decompress_file(gz.as_ptr(), out.as_ptr()),
+                RgzipStatus::Ok
+            );
+            assert_eq!(
+                std::fs::read(dir.join("a.out")).unwrap(),
+                std::fs::read(dir.join("a.txt")).unwrap()
+            );
+            let missing = path("missing");
+            assert_eq!(
+                rgzip_decompress_file(missing.as_ptr(), out.as_ptr()),
+                RgzipStatus::NotFound
+            );
+            assert_eq!(
+                rgzip_compress_file(ptr::null(), gz.as_ptr(), 6),
+                RgzipStatus::InvalidArgument
+            );
+            // 未压缩的文件当作 gzip 解压。
+            assert_eq!(
+                rgzip_decompress_file(a.as_ptr(), out.as_ptr()),
+                RgzipStatus::Corrupt
+            );
+            assert!(!dir.join("a.out").exists());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+/* 通过 C ABI 使用 rgzip：文件接口、流式句柄与错误码。用法：smoke <临时目录> */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "rgzip.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                          \
+    do {                                                                     \
+        if (!(cond)) {                                                       \
+            const char *err = rgzip_last_error();                            \
+            fprintf(stderr, "%s:%d: 检查失败: %s (last_error: %s)\n",        \
+                    __FILE__, __LINE__, #cond, err ? err : "NULL");          \
+            failures++;                                                      \
+        }                                                                    \
+    } while (0)
+
+static char *join(const char *dir, const char *name) {
+    size_t len = strlen(dir) + strlen(name) + 2;
+    char *path = malloc(len);
+    snprintf(path, len, "%s/%s", dir, name);
+    return path;
+}
+
+static unsigned char *read_all(const char *path, size_t *len) {
+    FILE *f = fopen(path, "rb");
+    if (!f) {
+        return NULL;
+    }
+    fseek(f, 0, SEEK_END);
+    *len = (size_t)ftell(f);
+    fseek(f, 0, SEEK_SET);
+    unsigned char *buf = malloc(*len + 1);
+    if (fread(buf, 1, *len, f) != *len) {
+        free(buf);
+        buf = NULL;
+    }
+    fclose(f);
+    return buf;
+}
+
+/* 把句柄中已产生的数据追加到 (*out, *len)。 */
+static void drain_encoder(RgzipEncoder *enc, unsigned char **out, size_t *len) {
+    unsigned char chunk[333];
+    size_t n;
+    while ((n = rgzip_encoder_read(enc, chunk, sizeof chunk)) > 0) {
+        *out = realloc(*out, *len + n);
+        memcpy(*out + *len, chunk, n);
+        *len += n;
+    }
+}
+
+/* 解压当前输入能产生的全部数据，返回最后一次读取的状态。 */
+static RgzipStatus drain_decoder(RgzipDecoder *dec, unsigned char **out, size_t *len) {
+    unsigned char chunk[333];
+    size_t n;
+    RgzipStatus status;
+    while ((status = rgzip_decoder_read(dec, chunk, sizeof chunk, &n)) == RGZIP_STATUS_OK &&
+           n > 0) {
+        *out = realloc(*out, *len + n);
+        memcpy(*out + *len, chunk, n);
+        *len += n;
+    }
+    return status;
+}
+
+static void test_files(const char *dir) {
+    char *plain = join(dir, "plain.txt");
+    char *gz = join(dir, "plain.txt.gz");
+    char *out = join(dir, "plain.out");
+    char *missing = join(dir, "missing.gz");
+
+    FILE *f = fopen(plain, "wb");
+    for (int i = 0; i < 1000; i++) {
+        fprintf(f, "line %d from C\n", i);
+    }
+    fclose(f);
+
+    CHECK(rgzip_compress_file(plain, gz, RGZIP_DEFAULT_LEVEL) == RGZIP_STATUS_OK);
+    CHECK(rgzip_last_error() == NULL);
+    CHECK(rgzip_decompress_file(gz, out) == RGZIP_STATUS_OK);
+
+    size_t a_len = 0, b_len = 0;
+    unsigned char *a = read_all(plain, &a_len);
+    unsigned char *b = read_all(out, &b_len);
+    CHECK(a && b && a_len == b_len && memcmp(a, b, a_len) == 0);
+    free(a);
+    free(b);
+
+    CHECK(rgzip_decompress_file(missing, out) == RGZIP_STATUS_NOT_FOUND);
+    CHECK(rgzip_last_error() != NULL);
+    CHECK(rgzip_decompress_file(plain, out) == RGZIP_STATUS_CORRUPT);
+    CHECK(rgzip_compress_file(plain, gz, 10) == RGZIP_STATUS_INVALID_ARGUMENT);
+    CHECK(rgzip_compress_file(NULL, gz, 6) == RGZIP_STATUS_INVALID_ARGUMENT);
+
+    free(plain);
+    free(gz);
+    free(out);
+    free(missing);
+}
+
+static void test_streams(void) {
+    size_t data_len = 1 << 20;
+    unsigned char *data = malloc(data_len);
+    for (size_t i = 0; i < data_len; i++) {
+        data[i] = (unsigned char)((i * 31) % 251);
+    }
+
+    RgzipEncoder *enc = rgzip_encoder_new(6);
+    CHECK(enc != NULL);
+    unsigned char *gz = NULL;
+    size_t gz_len = 0;
+    for (size_t off = 0; off < data_len; off += 1000) {
+        size_t n = data_len - off < 1000 ? data_len - off : 1000;
+        CHECK(rgzip_encoder_write(enc, data + off, n) == RGZIP_STATUS_OK);
+        drain_encoder(enc, &gz, &gz_len);
+    }
+    CHECK(rgzip_encoder_finish(enc) == RGZIP_STATUS_OK);
+    drain_encoder(enc, &gz, &gz_len);
+    CHECK(rgzip_encoder_write(enc, data, 1) == RGZIP_STATUS_INVALID_ARGUMENT);
+    rgzip_encoder_free(enc);
+    CHECK(gz_len > 18 && gz[0] == 0x1f && gz[1] == 0x8b);
+
+    /* 以 7 字节为单位写入解码器。 */
+    RgzipDecoder *dec = rgzip_decoder_new();
+    unsigned char *plain = NULL;
+    size_t plain_len = 0;
+    for (size_t off = 0; off < gz_len; off += 7) {
+        size_t n = gz_len - off < 7 ? gz_len - off : 7;
+        CHECK(rgzip_decoder_write(dec, gz + off, n) == RGZIP_STATUS_OK);
+        CHECK(drain_decoder(dec, &plain, &plain_len) == RGZIP_STATUS_OK);
+    }
+    CHECK(rgzip_decoder_finish(dec) == RGZIP_STATUS_OK);
+    CHECK(drain_decoder(dec, &plain, &plain_len) == RGZIP_STATUS_OK);
+    rgzip_decoder_free(dec);
+    CHECK(plain_len == data_len && memcmp(plain, data, data_len) == 0);
+
+    /* 截断的流在 finish 之后读尽时报告 TRUNCATED。 */
+    unsigned char *partial = NULL;
+    size_t partial_len = 0;
+    dec = rgzip_decoder_new();
+    CHECK(rgzip_decoder_write(dec, gz, gz_len / 2) == RGZIP_STATUS_OK);
+    CHECK(rgzip_decoder_finish(dec) == RGZIP_STATUS_OK);
+    CHECK(drain_decoder(dec, &partial, &partial_len) == RGZIP_STATUS_TRUNCATED);
+    rgzip_decoder_free(dec);
+    free(partial);
+
+    /* 非 gzip 数据在读取时报告 CORRUPT。 */
+    dec = rgzip_decoder_new();
+    const char junk[] = "definitely not gzip";
+    CHECK(rgzip_decoder_write(dec, (const uint8_t *)junk, sizeof junk) == RGZIP_STATUS_OK);
+    unsigned char byte;
+    size_t n = 0;
+    CHECK(rgzip_decoder_read(dec, &byte, 1, &n) == RGZIP_STATUS_CORRUPT);
+    rgzip_decoder_free(dec);
+
+    CHECK(rgzip_encoder_new(-5) == NULL);
+    CHECK(rgzip_decoder_finish(NULL) == RGZIP_STATUS_INVALID_ARGUMENT);
+    rgzip_encoder_free(NULL);
+    rgzip_decoder_free(NULL);
+
+    free(data);
+    free(gz);
+    free(plain);
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "用法: %s <临时目录>\n", argv[0]);
+        return 2;
+    }
+    printf("rgzip %s\n", rgzip_version());
+    test_files(argv[1]);
+    test_streams();
+    if (failures) {
+        fprintf(stderr, "%d 项检查失败\n", failures);
+        return 1;
+    }
+    printf("全部通过\n");
+    return 0;
+}
@@ -0,0 +1,65 @@
+//! 用系统 C 编译器（`$CC`，默认 `cc`）编译 `tests/c/smoke.c`，链接 cdylib 后运行。
+//! `cargo test` 不会生成 cdylib，因此测试先用同一 profile 构建它。
+
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 构建 cdylib 并返回其所在目录。cargo 把它放在 `<target>/<profile>/`，即测试可执行文件所在
+/// `deps/` 的上一级；构建沿用当前环境（含 `CARGO_TARGET_DIR`），输出因此落在同一目录。
+fn build_library(manifest: &Path) -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().and_then(Path::parent).unwrap().to_path_buf();
+    let profile = match lib_dir.file_name().and_then(|s| s.to_str()) {
+        Some("debug") | None => "dev",
+        Some(name) => name,
+    };
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(&cargo)
+        .args(["build", "--lib", "--profile", profile, "--manifest-path"])
+        .arg(manifest.join("Cargo.toml"))
+        .status()
+        .unwrap_or_else(|e| panic!("无法运行 {}: {}", cargo, e));
+    assert!(status.success(), "构建 rgzip-capi 失败");
+    lib_dir
+}
+
+#[test]
+fn test_c_program() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = build_library(manifest);
+    let work = env::temp_dir().join(format!("rgzip-capi-c-{}", std::process::id()));
+    fs::create_dir_all(&work).unwrap();
+    let exe = work.join("smoke");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(manifest.join("tests/c/smoke.c"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lrgzip_capi")
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .unwrap_or_else(|e| panic!("无法运行 C 编译器 {}: {}", cc, e));
+    assert!(status.success(), "编译 smoke.c 失败");
+
+    let output = Command::new(&exe).arg(&work).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "smoke 失败\nstdout:\n{}\nstderr:\n{}",
+        stdout,
+        stderr
+    );
+    assert!(stdout.contains(concat!("rgzip ", env!("CARGO_PKG_VERSION"))));
+    fs::remove_dir_all(&work).unwrap();
+}
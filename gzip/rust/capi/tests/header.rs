@@ -0,0 +1,30 @@
+//! `include/rgzip.h` 必须与 cbindgen 根据当前源码生成的结果一致。
+//! 修改 C 接口后运行 `RGZIP_BLESS=1 cargo test -p rgzip-capi --test header` 更新头文件。
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_header_up_to_date() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+    let bindings = cbindgen::Builder::new()
+        .with_crate(dir)
+        .with_config(config)
+        .generate()
+        .expect("cbindgen 生成失败");
+    let mut generated = Vec::new();
+    bindings.write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let header = dir.join("include/rgzip.h");
+    if std::env::var_os("RGZIP_BLESS").is_some() {
+        fs::write(&header, &generated).unwrap();
+        return;
+    }
+    let committed = fs::read_to_string(&header).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "include/rgzip.h 已过期，请运行 RGZIP_BLESS=1 cargo test -p rgzip-capi --test header"
+    );
+}
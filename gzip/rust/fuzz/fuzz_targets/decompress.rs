@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// 任意输入都只能返回错误，不能 panic；限制输出避免高压缩比样本拖慢 fuzz。
+fuzz_target!(|data: &[u8]| {
+    let limits = rgzip::DecompressLimits {
+        max_output: Some(16 << 20),
+        ..Default::default()
+    };
+    let _ = rgzip::decompress_reader_to_writer_with_limits(data, std::io::sink(), limits);
+});
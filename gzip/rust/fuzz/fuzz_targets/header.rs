@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = rgzip::read_gz_header(data) {
+        assert!(header.len <= data.len());
+    }
+});
@@ -0,0 +1,490 @@
+//! rgzip 的 Python 绑定（PyO3），模块名为 `rgzip`。
+//!
+//! 文件级接口 `compress_path` / `decompress_path` 释放 GIL 后调用 rgzip 库；`open` 返回类文件对象
+//! [`GzipFile`]，可包装路径或任意带 `read` / `write` 方法的二进制文件对象。
+//! 数据损坏报告为 `rgzip.BadGzipFile`，流不完整报告为 `EOFError`，与标准库 `gzip` 模块一致。
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::{Compression, GzBuilder};
+use pyo3::exceptions::{PyEOFError, PyFileExistsError, PyOSError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pybacked::PyBackedBytes;
+use pyo3::types::PyBytes;
+use pyo3::{create_exception, import_exception};
+use rgzip::{compress_path_with_options, decompress_path_with_options, read_gz_header};
+use rgzip::{default_output_for_compress, default_output_for_decompress, same_path};
+use rgzip::{CompressOptions, DecompressLimits, DecompressOptions, GzHeader, HOST_OS, MAX_LEVEL};
+
+create_exception!(
+    rgzip,
+    BadGzipFile,
+    PyOSError,
+    "数据不是合法的 gzip 流或校验失败。"
+);
+create_exception!(
+    rgzip,
+    LimitExceeded,
+    PyOSError,
+    "解压输出超过 max_output 或 max_ratio 限制。"
+);
+import_exception!(io, UnsupportedOperation);
+
+/// 将 rgzip 的 I/O 错误转换为 Python 异常；Python 文件对象抛出的异常原样传回。
+fn to_py(e: io::Error) -> PyErr {
+    if e.get_ref().is_some_and(|inner| inner.is::<PyErr>()) {
+        return e.into();
+    }
+    if let Some(limit) = rgzip::LimitExceeded::from_io(&e) {
+        return LimitExceeded::new_err(limit.to_string());
+    }
+    match e.kind() {
+        // flate2 以 InvalidInput 报告损坏的 gzip 数据。
+        io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput => {
+            BadGzipFile::new_err(e.to_string())
+        }
+        io::ErrorKind::UnexpectedEof => PyEOFError::new_err(format!("gzip 流不完整: {}", e)),
+        _ => e.into(),
+    }
+}
+
+fn check_level(level: u32) -> PyResult<u32> {
+    if level > MAX_LEVEL {
+        return Err(PyValueError::new_err(format!(
+            "压缩级别应为 0-{}: {}",
+            MAX_LEVEL, level
+        )));
+    }
+    Ok(level)
+}
+
+fn check_output(input: &Path, output: &Path, force: bool) -> PyResult<()> {
+    if same_path(input, output) {
+        return Err(PyValueError::new_err(format!(
+            "输入与输出为同一文件: {}",
+            output.display()
+        )));
+    }
+    if !force && output.exists() {
+        return Err(PyFileExistsError::new_err(format!(
+            "目标已存在（使用 force=True 覆盖）: {}",
+            output.display()
+        )));
+    }
+    Ok(())
+}
+
+/// 压缩文件，返回输出路径（默认 `<input>.gz`）。原文件保留。
+#[pyfunction]
+#[pyo3(signature = (input, output=None, level=6, *, no_name=false, force=false))]
+fn compress_path(
+    py: Python<'_>,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    level: u32,
+    no_name: bool,
+    force: bool,
+) -> PyResult<PathBuf> {
+    let opts = CompressOptions {
+        level: check_level(level)?,
+        no_name,
+        ..Default::default()
+    };
+    let output = output.unwrap_or_else(|| default_output_for_compress(&input));
+    check_output(&input, &output, force)?;
+    py.allow_threads(|| compress_path_with_options(&input, &output, &opts))
+        .map_err(to_py)?;
+    Ok(output)
+}
+
+/// 解压文件，返回输出路径（默认去掉 `.gz`，否则追加 `.out`）。失败时删除不完整的输出。
+#[pyfunction]
+#[pyo3(signature = (input, output=None, *, force=false, max_output=None, max_ratio=None))]
+fn decompress_path(
+    py: Python<'_>,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    force: bool,
+    max_output: Option<u64>,
+    max_ratio: Option<u64>,
+) -> PyResult<PathBuf> {
+    let opts = DecompressOptions {
+        limits: DecompressLimits {
+            max_output,
+            max_ratio,
+        },
+        ..Default::default()
+    };
+    let output = output.unwrap_or_else(|| default_output_for_decompress(&input));
+    check_output(&input, &output, force)?;
+    py.allow_threads(|| decompress_path_with_options(&input, &output, &opts))
+        .map_err(to_py)?;
+    Ok(output)
+}
+
+/// gzip 成员头；文件名与注释按 UTF-8（有损）解码。
+#[pyclass(frozen, module = "rgzip")]
+struct GzipHeader(GzHeader);
+
+#[pymethods]
+impl GzipHeader {
+    #[getter]
+    fn filename(&self) -> Option<String> {
+        self.0.filename_lossy()
+    }
+
+    #[getter]
+    fn comment(&self) -> Option<String> {
+        self.0
+            .comment
+            .as_ref()
+            .map(|c| String::from_utf8_lossy(c).into_owned())
+    }
+
+    #[getter]
+    fn mtime(&self) -> u32 {
+        self.0.mtime
+    }
+
+    #[getter]
+    fn os(&self) -> u8 {
+        self.0.os
+    }
+
+    #[getter]
+    fn extra<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.0.extra.as_ref().map(|e| PyBytes::new(py, e))
+    }
+
+    #[getter]
+    fn is_text(&self) -> bool {
+        self.0.is_text()
+    }
+
+    #[getter]
+    fn header_crc(&self) -> Option<u16> {
+        self.0.header_crc
+    }
+
+    /// 头部总字节数。
+    #[getter]
+    fn size(&self) -> usize {
+        self.0.len
+    }
+
+    fn __repr__(&self) -> String {
+        let filename = self
+            .filename()
+            .map_or_else(|| "None".to_string(), |n| format!("{:?}", n));
+        format!(
+            "GzipHeader(filename={}, mtime={}, os={})",
+            filename, self.0.mtime, self.0.os
+        )
+    }
+}
+
+/// 读取第一个成员的 gzip 头；`source` 为路径或 `bytes`。
+#[pyfunction]
+fn read_header(py: Python<'_>, source: &Bound<'_, PyAny>) -> PyResult<GzipHeader> {
+    let header = if let Ok(data) = source.downcast::<PyBytes>() {
+        read_gz_header(data.as_bytes())
+    } else {
+        let path: PathBuf = source.extract()?;
+        py.allow_threads(|| File::open(&path).and_then(|f| read_gz_header(BufReader::new(f))))
+    };
+    header.map(GzipHeader).map_err(to_py)
+}
+
+/// 压缩数据的来源：文件或 Python 二进制文件对象。
+enum Source {
+    File(File),
+    Object(Py<PyAny>),
+}
+
+impl Read for Source {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Source::File(f) => f.read(buf),
+            Source::Object(obj) => Python::with_gil(|py| -> PyResult<usize> {
+                let data: PyBackedBytes =
+                    obj.call_method1(py, "read", (buf.len(),))?.extract(py)?;
+                if data.len() > buf.len() {
+                    return Err(PyValueError::new_err("read() 返回的数据多于请求的长度"));
+                }
+                buf[..data.len()].copy_from_slice(&data);
+                Ok(data.len())
+            })
+            .map_err(io::Error::from),
+        }
+    }
+}
+
+/// 压缩数据的去向：文件或 Python 二进制文件对象。
+enum Sink {
+    File(File),
+    Object(Py<PyAny>),
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::File(f) => f.write(buf),
+            Sink::Object(obj) => Python::with_gil(|py| -> PyResult<usize> {
+                obj.call_method1(py, "write", (PyBytes::new(py, buf),))?;
+                Ok(buf.len())
+            })
+            .map_err(io::Error::from),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::File(f) => f.flush(),
+            Sink::Object(obj) => Python::with_gil(|py| -> PyResult<()> {
+                if obj.bind(py).hasattr("flush")? {
+                    obj.call_method0(py, "flush")?;
+                }
+                Ok(())
+            })
+            .map_err(io::Error::from),
+        }
+    }
+}
+
+enum Stream {
+    Reader(BufReader<MultiGzDecoder<Source>>),
+    Writer(GzEncoder<Sink>),
+    Closed,
+}
+
+impl Stream {
+    fn reader(&mut self) -> PyResult<&mut BufReader<MultiGzDecoder<Source>>> {
+        match self {
+            Stream::Reader(r) => Ok(r),
+            Stream::Writer(_) => Err(UnsupportedOperation::new_err("文件未以读模式打开")),
+            Stream::Closed => Err(closed()),
+        }
+    }
+
+    fn writer(&mut self) -> PyResult<&mut GzEncoder<Sink>> {
+        match self {
+            Stream::Writer(w) => Ok(w),
+            Stream::Reader(_) => Err(UnsupportedOperation::new_err("文件未以写模式打开")),
+            Stream::Closed => Err(closed()),
+        }
+    }
+}
+
+fn closed() -> PyErr {
+    PyValueError::new_err("I/O operation on closed file.")
+}
+
+/// 读取 `limit` 字节（`None` 为不限）；`line` 为真时读到换行符为止。
+fn read_limited<R: BufRead>(reader: &mut R, limit: Option<u64>, line: bool) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut reader = reader.take(limit.unwrap_or(u64::MAX));
+    if line {
+        reader.read_until(b'\n', &mut out)?;
+    } else {
+        reader.read_to_end(&mut out)?;
+    }
+    Ok(out)
+}
+
+/// 类文件对象：读模式下依次解码所有 gzip 成员，写模式下写出单个 gzip 成员。
+///
+/// 所有 I/O 都在释放 GIL 后进行；对象可跨线程使用，内部以互斥锁串行化。
+#[pyclass(frozen, module = "rgzip")]
+struct GzipFile {
+    stream: Mutex<Stream>,
+    mode: &'static str,
+}
+
+impl GzipFile {
+    /// 释放 GIL 后在锁内操作流（先释放 GIL 再加锁，避免与持有 GIL 的线程互相等待）。
+    fn with_stream<T: Send>(
+        &self,
+        py: Python<'_>,
+        f: impl FnOnce(&mut Stream) -> PyResult<T> + Send,
+    ) -> PyResult<T> {
+        py.allow_threads(|| {
+            let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&mut stream)
+        })
+    }
+
+    fn read_bytes<'py>(
+        &self,
+        py: Python<'py>,
+        size: Option<i64>,
+        line: bool,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let limit = size.filter(|&n| n >= 0).map(|n| n as u64);
+        let data = self.with_stream(py, |s| {
+            read_limited(s.reader()?, limit, line).map_err(to_py)
+        })?;
+        Ok(PyBytes::new(py, &data))
+    }
+}
+
+#[pymethods]
+impl GzipFile {
+    /// 读取至多 `size` 字节解压数据；省略或为负数时读到末尾。
+    #[pyo3(signature = (size=-1))]
+    fn read<'py>(&self, py: Python<'py>, size: Option<i64>) -> PyResult<Bound<'py, PyBytes>> {
+        self.read_bytes(py, size, false)
+    }
+
+    /// 读取一行（含换行符），至多 `size` 字节。
+    #[pyo3(signature = (size=-1))]
+    fn readline<'py>(&self, py: Python<'py>, size: Option<i64>) -> PyResult<Bound<'py, PyBytes>> {
+        self.read_bytes(py, size, true)
+    }
+
+    /// 写入数据，返回写入的字节数。
+    fn write(&self, py: Python<'_>, data: PyBackedBytes) -> PyResult<usize> {
+        self.with_stream(py, |s| {
+            s.writer()?.write_all(&data).map_err(to_py)?;
+            Ok(data.len())
+        })
+    }
+
+    /// 写模式下同步刷新已写入的数据，使其可被解码。
+    fn flush(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_stream(py, |s| match s {
+            Stream::Writer(w) => w.flush().map_err(to_py),
+            Stream::Reader(_) => Ok(()),
+            Stream::Closed => Err(closed()),
+        })
+    }
+
+    /// 结束流；写模式下写出 gzip 尾部。重复调用无效果，包装的文件对象不会被关闭。
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_stream(py, |s| match std::mem::replace(s, Stream::Closed) {
+            Stream::Writer(w) => w.finish().and_then(|mut sink| sink.flush()).map_err(to_py),
+            Stream::Reader(_) | Stream::Closed => Ok(()),
+        })
+    }
+
+    #[getter]
+    fn closed(&self, py: Python<'_>) -> PyResult<bool> {
+        self.with_stream(py, |s| Ok(matches!(s, Stream::Closed)))
+    }
+
+    #[getter]
+    fn mode(&self) -> &'static str {
+        self.mode
+    }
+
+    fn readable(&self) -> bool {
+        self.mode == "rb"
+    }
+
+    fn writable(&self) -> bool {
+        self.mode != "rb"
+    }
+
+    fn seekable(&self) -> bool {
+        false
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc: &Bound<'_, PyAny>,
+        _tb: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+
+    fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __next__<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let line = self.read_bytes(py, None, true)?;
+        Ok((!line.as_bytes().is_empty()).then_some(line))
+    }
+}
+
+/// 打开 gzip 文件。`file` 为路径或二进制文件对象；`mode` 为 `rb`、`wb`、`ab`、`xb`（`b` 可省略）。
+///
+/// 写出的 gzip 头不含文件名，mtime 为 0，相同内容得到相同字节。
+#[pyfunction]
+#[pyo3(signature = (file, mode="rb", level=6))]
+fn open(file: &Bound<'_, PyAny>, mode: &str, level: u32) -> PyResult<GzipFile> {
+    let mode = match mode {
+        "r" | "rb" => "rb",
+        "w" | "wb" => "wb",
+        "a" | "ab" => "ab",
+        "x" | "xb" => "xb",
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "不支持的模式: {:?}（仅支持二进制模式 rb/wb/ab/xb）",
+                mode
+            )))
+        }
+    };
+    let level = check_level(level)?;
+    let path = file.extract::<PathBuf>().ok();
+    let method = if mode == "rb" { "read" } else { "write" };
+    if path.is_none() && !file.hasattr(method)? {
+        return Err(PyTypeError::new_err(format!(
+            "file 应为路径或带 {}() 方法的二进制文件对象",
+            method
+        )));
+    }
+    let stream = if mode == "rb" {
+        let source = match path {
+            Some(path) => Source::File(File::open(path)?),
+            None => Source::Object(file.clone().unbind()),
+        };
+        Stream::Reader(BufReader::new(MultiGzDecoder::new(source)))
+    } else {
+        let sink = match path {
+            Some(path) => {
+                let mut options = OpenOptions::new();
+                match mode {
+                    "ab" => options.append(true).create(true),
+                    "xb" => options.write(true).create_new(true),
+                    _ => options.write(true).create(true).truncate(true),
+                };
+                Sink::File(options.open(path)?)
+            }
+            None => Sink::Object(file.clone().unbind()),
+        };
+        let builder = GzBuilder::new().mtime(0).operating_system(HOST_OS);
+        Stream::Writer(builder.write(sink, Compression::new(level)))
+    };
+    Ok(GzipFile {
+        stream: Mutex::new(stream),
+        mode,
+    })
+}
+
+#[pymodule]
+#[pyo3(name = "rgzip")]
+fn rgzip_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("BadGzipFile", py.get_type::<BadGzipFile>())?;
+    m.add("LimitExceeded", py.get_type::<LimitExceeded>())?;
+    m.add_class::<GzipHeader>()?;
+    m.add_class::<GzipFile>()?;
+    m.add_function(wrap_pyfunction!(compress_path, m)?)?;
+    m.add_function(wrap_pyfunction!(decompress_path, m)?)?;
+    m.add_function(wrap_pyfunction!(read_header, m)?)?;
+    m.add_function(wrap_pyfunction!(open, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+"""未安装 rgzip 时（如未运行 `maturin develop`），用 cargo 构建扩展模块并加入 sys.path。
+
+构建只使用本地已有的依赖与工具链，测试本身不访问网络。
+"""
+
+import importlib.util
+import json
+import shutil
+import subprocess
+import sys
+import sysconfig
+import tempfile
+from pathlib import Path
+
+CRATE_DIR = Path(__file__).resolve().parent.parent
+
+
+def _build_extension():
+    proc = subprocess.run(
+        ["cargo", "build", "--message-format=json-render-diagnostics"],
+        cwd=CRATE_DIR,
+        stdout=subprocess.PIPE,
+        check=True,
+        text=True,
+    )
+    for line in proc.stdout.splitlines():
+        msg = json.loads(line)
+        if msg.get("reason") == "compiler-artifact" and msg["target"]["name"] == "rgzip_py":
+            for name in msg["filenames"]:
+                if name.endswith((".so", ".dylib", ".dll")):
+                    return Path(name)
+    raise RuntimeError("cargo 未生成 rgzip_py 动态库")
+
+
+def _ensure_rgzip():
+    if importlib.util.find_spec("rgzip") is not None:
+        return
+    library = _build_extension()
+    suffix = ".pyd" if sys.platform == "win32" else ".so"
+    module_dir = Path(tempfile.mkdtemp(prefix="rgzip-py-"))
+    # 动态库须以模块名命名才能被导入；EXT_SUFFIX 之外的平台后缀同样可用。
+    shutil.copy(library, module_dir / ("rgzip" + (sysconfig.get_config_var("EXT_SUFFIX") or suffix)))
+    sys.path.insert(0, str(module_dir))
+
+
+_ensure_rgzip()
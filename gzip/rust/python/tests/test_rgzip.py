@@ -0,0 +1,214 @@
+import gzip
+import io
+import os
+import threading
+import zlib
+
+import pytest
+
+import rgzip
+
+DATA = b"rgzip python binding\n" * 2000
+
+
+def test_compress_path_default_output(tmp_path):
+    src = tmp_path / "data.txt"
+    src.write_bytes(DATA)
+    out = rgzip.compress_path(src)
+    assert os.fspath(out) == os.fspath(tmp_path / "data.txt.gz")
+    assert src.exists()
+    assert gzip.decompress((tmp_path / "data.txt.gz").read_bytes()) == DATA
+    header = rgzip.read_header(out)
+    assert header.filename == "data.txt"
+    assert header.mtime == int(src.stat().st_mtime)
+
+
+def test_compress_path_options(tmp_path):
+    src = tmp_path / "data.txt"
+    src.write_bytes(DATA)
+    dst = tmp_path / "custom.gz"
+    rgzip.compress_path(str(src), str(dst), 9, no_name=True)
+    header = rgzip.read_header(dst)
+    assert (header.filename, header.mtime) == (None, 0)
+    assert gzip.decompress(dst.read_bytes()) == DATA
+
+    with pytest.raises(FileExistsError):
+        rgzip.compress_path(src, dst)
+    rgzip.compress_path(src, dst, level=1, force=True)
+    with pytest.raises(ValueError):
+        rgzip.compress_path(src, tmp_path / "bad.gz", level=10)
+    with pytest.raises(ValueError):
+        rgzip.compress_path(src, src)
+    with pytest.raises(FileNotFoundError):
+        rgzip.compress_path(tmp_path / "missing.txt")
+
+
+def test_decompress_path(tmp_path):
+    gz = tmp_path / "data.txt.gz"
+    gz.write_bytes(gzip.compress(DATA[:100]) + gzip.compress(DATA[100:]))
+    out = rgzip.decompress_path(gz)
+    assert os.fspath(out) == os.fspath(tmp_path / "data.txt")
+    assert (tmp_path / "data.txt").read_bytes() == DATA
+    with pytest.raises(FileExistsError):
+        rgzip.decompress_path(gz)
+
+    other = tmp_path / "other"
+    other.write_bytes(gz.read_bytes())
+    assert os.fspath(rgzip.decompress_path(other)) == os.fspath(tmp_path / "other.out")
+
+
+def test_decompress_path_errors(tmp_path):
+    bad = tmp_path / "bad.gz"
+    bad.write_bytes(b"not gzip at all")
+    with pytest.raises(rgzip.BadGzipFile):
+        rgzip.decompress_path(bad)
+    assert not (tmp_path / "bad").exists()
+
+    cut = tmp_path / "cut.gz"
+    cut.write_bytes(gzip.compress(DATA)[:-20])
+    with pytest.raises(EOFError):
+        rgzip.decompress_path(cut)
+    assert not (tmp_path / "cut").exists()
+
+    gz = tmp_path / "data.gz"
+    gz.write_bytes(gzip.compress(DATA))
+    with pytest.raises(rgzip.LimitExceeded):
+        rgzip.decompress_path(gz, max_output=1000)
+    assert issubclass(rgzip.LimitExceeded, OSError)
+    assert issubclass(rgzip.BadGzipFile, OSError)
+    rgzip.decompress_path(gz, max_output=len(DATA))
+
+
+def test_read_header_fields(tmp_path):
+    buf = io.BytesIO()
+    with gzip.GzipFile(filename="orig.csv", mode="wb", fileobj=buf, mtime=1700000000) as f:
+        f.write(b"a,b\n")
+    data = buf.getvalue()
+    header = rgzip.read_header(data)
+    assert header.filename == "orig.csv"
+    assert header.mtime == 1700000000
+    assert header.comment is None and header.extra is None and header.header_crc is None
+    assert header.size == 10 + len("orig.csv") + 1
+    assert "orig.csv" in repr(header)
+
+    path = tmp_path / "h.gz"
+    path.write_bytes(data)
+    assert rgzip.read_header(path).filename == "orig.csv"
+    with pytest.raises(rgzip.BadGzipFile):
+        rgzip.read_header(b"\x00" * 20)
+    with pytest.raises(EOFError):
+        rgzip.read_header(data[:5])
+
+
+def test_open_path_roundtrip(tmp_path):
+    path = tmp_path / "stream.gz"
+    with rgzip.open(path, "wb", level=9) as f:
+        assert f.writable() and not f.readable() and not f.seekable()
+        for i in range(0, len(DATA), 777):
+            assert f.write(DATA[i : i + 777]) == len(DATA[i : i + 777])
+        f.write(bytearray(b"tail\n"))
+    assert f.closed
+    assert gzip.decompress(path.read_bytes()) == DATA + b"tail\n"
+
+    with rgzip.open(path, "ab") as f:
+        f.write(b"appended\n")
+    with rgzip.open(path) as f:
+        assert f.mode == "rb"
+        head = f.read(5)
+        assert head == DATA[:5]
+        assert f.readline() == DATA[5:21]
+        assert f.read() == DATA[21:] + b"tail\n" + b"appended\n"
+        assert f.read() == b""
+
+    with pytest.raises(FileExistsError):
+        rgzip.open(path, "xb")
+
+
+def test_open_reads_stdlib_output_line_by_line(tmp_path):
+    path = tmp_path / "lines.gz"
+    lines = [b"first\n", b"second\n", b"no newline"]
+    with gzip.open(path, "wb") as f:
+        f.writelines(lines)
+    with rgzip.open(os.fspath(path)) as f:
+        assert list(f) == lines
+    with rgzip.open(path) as f:
+        assert f.readline(3) == b"fir"
+
+
+def test_open_file_objects():
+    raw = io.BytesIO()
+    with rgzip.open(raw, "wb") as f:
+        f.write(DATA)
+        f.flush()
+        # 同步刷新后已写出的数据即可解码。
+        assert zlib.decompressobj(31).decompress(raw.getvalue()) == DATA
+    assert not raw.closed
+    assert gzip.decompress(raw.getvalue()) == DATA
+
+    # 相同内容得到相同字节（头部不含文件名与时间）。
+    outputs = []
+    for _ in range(2):
+        out = io.BytesIO()
+        with rgzip.open(out, "w") as f:
+            f.write(DATA)
+        outputs.append(out.getvalue())
+    assert outputs[0] == outputs[1]
+    assert rgzip.read_header(outputs[0]).mtime == 0
+
+    with rgzip.open(io.BytesIO(raw.getvalue()), "rb") as f:
+        assert f.read() == DATA
+
+
+def test_open_errors(tmp_path):
+    path = tmp_path / "e.gz"
+    with pytest.raises(ValueError):
+        rgzip.open(path, "wt")
+    with pytest.raises(TypeError):
+        rgzip.open(42)
+
+    f = rgzip.open(path, "wb")
+    with pytest.raises(io.UnsupportedOperation):
+        f.read()
+    f.close()
+    f.close()
+    with pytest.raises(ValueError):
+        f.write(b"x")
+
+    with rgzip.open(io.BytesIO(gzip.compress(DATA)[:-20])) as f:
+        with pytest.raises(EOFError):
+            f.read()
+    with rgzip.open(io.BytesIO(b"\x1f\x8b\x08\x00garbage-garbage")) as f:
+        with pytest.raises(rgzip.BadGzipFile):
+            f.read()
+
+
+def test_file_object_exception_propagates():
+    class Broken(io.RawIOBase):
+        def read(self, size=-1):
+            raise RuntimeError("boom")
+
+    with rgzip.open(Broken()) as f:
+        with pytest.raises(RuntimeError, match="boom"):
+            f.read()
+
+
+def test_concurrent_calls(tmp_path):
+    errors = []
+
+    def work(i):
+        try:
+            src = tmp_path / f"t{i}.txt"
+            src.write_bytes(DATA * (i + 1))
+            gz = rgzip.compress_path(src)
+            out = rgzip.decompress_path(gz, tmp_path / f"t{i}.out")
+            with open(out, "rb") as f:
+                assert f.read() == DATA * (i + 1)
+        except Exception as e:
+            errors.append(e)
+
+    threads = [threading.Thread(target=work, args=(i,)) for i in range(4)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+    assert errors == []
@@ -0,0 +1,181 @@
+use std::io::{self, Write};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{gz_encoder, GzPushDecoder, HeaderInfo};
+
+const CHUNK: usize = 64 * 1024;
+
+/// 将编码器累积的输出写入异步写入器并清空缓冲，使内存占用保持在单个块的量级。
+async fn drain<W: AsyncWrite + Unpin>(pending: &mut Vec<u8>, writer: &mut W) -> io::Result<()> {
+    if !pending.is_empty() {
+        writer.write_all(pending).await?;
+        pending.clear();
+    }
+    Ok(())
+}
+
+/// [`compress_reader_to_writer`](crate::compress_reader_to_writer) 的异步版本。
+///
+/// 压缩本身仍由同步编码器在内存缓冲上完成，与同步接口共用头部构造逻辑。
+/// 返回的写入器已 flush，但未 shutdown。
+pub async fn compress_reader_to_writer_async<R, W>(
+    mut reader: R,
+    mut writer: W,
+    level: u32,
+) -> io::Result<W>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut encoder = gz_encoder(Vec::new(), level, &HeaderInfo::default());
+    let mut buf = vec![0u8; CHUNK];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        encoder.write_all(&buf[..n])?;
+        drain(encoder.get_mut(), &mut writer).await?;
+    }
+    let mut rest = encoder.finish()?;
+    drain(&mut rest, &mut writer).await?;
+    writer.flush().await?;
+    Ok(writer)
+}
+
+/// [`decompress_reader_to_writer`](crate::decompress_reader_to_writer) 的异步版本，
+/// 同样完整解压多成员（multi-member）gzip 流。
+///
+/// 与同步接口共用同一个推送式解码器，错误类型一致；解压数据经定长缓冲区写出，
+/// 内存占用与输入的压缩比无关。
+pub async fn decompress_reader_to_writer_async<R, W>(mut reader: R, mut writer: W) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut decoder = GzPushDecoder::new();
+    let mut input = vec![0u8; CHUNK];
+    let mut output = vec![0u8; CHUNK];
+    loop {
+        match decoder.read(&mut output)? {
+            Some(0) => return writer.flush().await,
+            Some(n) => writer.write_all(&output[..n]).await?,
+            None => match reader.read(&mut input).await? {
+                0 => decoder.finish(),
+                n => decoder.push(&input[..n]),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compress_reader_to_writer, decompress_reader_to_writer};
+
+    fn sample() -> Vec<u8> {
+        (0..200_000u32)
+            .flat_map(|i| (i % 251).to_le_bytes())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip_over_duplex() {
+        let data = sample();
+        let (client, server) = tokio::io::duplex(4096);
+        let input = data.clone();
+        let producer = tokio::spawn(async move {
+            let mut w = compress_reader_to_writer_async(&input[..], client, 6)
+                .await
+                .unwrap();
+            w.shutdown().await.unwrap();
+        });
+
+        let mut out = Vec::new();
+        decompress_reader_to_writer_async(server, &mut out)
+            .await
+            .unwrap();
+        producer.await.unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[tokio::test]
+    async fn test_matches_sync_output() {
+        let data = sample();
+        let sync = compress_reader_to_writer(&data[..], Vec::new(), 9).unwrap();
+        let mut out = Vec::new();
+        compress_reader_to_writer_async(&data[..], &mut out, 9)
+            .await
+            .unwrap();
+        assert_eq!(out, sync);
+
+        let mut plain = Vec::new();
+        decompress_reader_to_writer(&out[..], &mut plain).unwrap();
+        assert_eq!(plain, data);
+    }
+
+    #[tokio::test]
+    async fn test_multi_member_and_corrupt() {
+        let mut joined = compress_reader_to_writer(&b"abc"[..], Vec::new(), 6).unwrap();
+        joined.extend(compress_reader_to_writer(&b"def"[..], Vec::new(), 1).unwrap());
+        let mut out = Vec::new();
+        decompress_reader_to_writer_async(&joined[..], &mut out)
+            .await
+            .unwrap();
+        assert_eq!(out, b"abcdef");
+
+        let truncated = &joined[..joined.len() - 4];
+        let mut out = Vec::new();
+        assert!(decompress_reader_to_writer_async(truncated, &mut out)
+            .await
+            .is_err());
+    }
+
+    /// 记录单次写入最大长度的异步写入器。
+    #[derive(Default)]
+    struct MaxWrite {
+        total: usize,
+        max: usize,
+    }
+
+    impl AsyncWrite for MaxWrite {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            self.total += buf.len();
+            self.max = self.max.max(buf.len());
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompress_output_is_bounded() {
+        // 64 MiB 的零压缩后只有几十 KiB，单块输入即可膨胀上千倍。
+        let zeros = vec![0u8; 64 << 20];
+        let bomb = compress_reader_to_writer(&zeros[..], Vec::new(), 9).unwrap();
+        assert!(bomb.len() < CHUNK * 2);
+        let mut sink = MaxWrite::default();
+        decompress_reader_to_writer_async(&bomb[..], &mut sink)
+            .await
+            .unwrap();
+        assert_eq!(sink.total, zeros.len());
+        assert!(sink.max <= CHUNK, "单次输出 {} 字节", sink.max);
+    }
+}
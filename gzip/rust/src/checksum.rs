@@ -0,0 +1,316 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use sha2::{Digest, Sha256};
+
+/// 校验和算法。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Crc32,
+    Blake3,
+}
+
+impl ChecksumAlgorithm {
+    /// 全部算法，按 [`find_sidecar`] 的查找顺序排列。
+    pub const ALL: [ChecksumAlgorithm; 3] = [
+        ChecksumAlgorithm::Sha256,
+        ChecksumAlgorithm::Blake3,
+        ChecksumAlgorithm::Crc32,
+    ];
+
+    /// 算法名，同时也是旁路文件的扩展名（`file.gz.sha256`）。
+    pub fn name(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Crc32 => "crc32",
+            ChecksumAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    pub fn hasher(self) -> Hasher {
+        match self {
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            ChecksumAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChecksumAlgorithm::ALL
+            .into_iter()
+            .find(|a| a.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("不支持的校验和算法: {}（可选 sha256、crc32、blake3）", s))
+    }
+}
+
+/// 增量计算校验和。
+#[derive(Clone)]
+pub enum Hasher {
+    Sha256(Sha256),
+    Crc32(crc32fast::Hasher),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    /// 返回小写十六进制摘要；CRC32 为 8 位大端十六进制。
+    pub fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Sha256(h) => to_hex(&h.finalize()),
+            Hasher::Crc32(h) => format!("{:08x}", h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 在读取时同步计算校验和的读取器；未设置算法时只透传数据。
+pub(crate) struct HashingReader<R> {
+    inner: R,
+    hasher: Option<Hasher>,
+}
+
+impl<R> HashingReader<R> {
+    pub(crate) fn new(inner: R, algorithm: Option<ChecksumAlgorithm>) -> Self {
+        HashingReader {
+            inner,
+            hasher: algorithm.map(ChecksumAlgorithm::hasher),
+        }
+    }
+
+    pub(crate) fn finalize_hex(self) -> Option<String> {
+        self.hasher.map(Hasher::finalize_hex)
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(h) = &mut self.hasher {
+            h.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// 在写入时同步计算校验和的写入器；未设置算法时只透传数据。
+pub(crate) struct HashingWriter<W> {
+    inner: W,
+    hasher: Option<Hasher>,
+}
+
+impl<W> HashingWriter<W> {
+    pub(crate) fn new(inner: W, algorithm: Option<ChecksumAlgorithm>) -> Self {
+        HashingWriter {
+            inner,
+            hasher: algorithm.map(ChecksumAlgorithm::hasher),
+        }
+    }
+
+    /// 对已直接取得的数据（如内存映射）计算校验和。
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        if let Some(h) = &mut self.hasher {
+            h.update(data);
+        }
+    }
+
+    pub(crate) fn finalize_hex(self) -> Option<String> {
+        self.hasher.map(Hasher::finalize_hex)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 一次压缩/解压过程中得到的两个摘要。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digests {
+    pub algorithm: ChecksumAlgorithm,
+    /// 压缩数据（`.gz` 文件）的摘要。
+    pub compressed: String,
+    /// 未压缩内容的摘要。
+    pub uncompressed: String,
+}
+
+/// 旁路校验文件路径：`<gz>.<算法名>`。
+pub fn sidecar_path(gz: &Path, algorithm: ChecksumAlgorithm) -> PathBuf {
+    let mut name = gz.as_os_str().to_os_string();
+    name.push(".");
+    name.push(algorithm.name());
+    PathBuf::from(name)
+}
+
+/// 按 [`ChecksumAlgorithm::ALL`] 的顺序查找已存在的旁路校验文件。
+pub fn find_sidecar(gz: &Path) -> Option<(PathBuf, ChecksumAlgorithm)> {
+    ChecksumAlgorithm::ALL
+        .into_iter()
+        .map(|a| (sidecar_path(gz, a), a))
+        .find(|(p, _)| p.is_file())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// 写入旁路校验文件，格式与 `sha256sum` / `b3sum` 的输出一致（`<摘要>  <文件名>`）：
+/// 第一行为压缩文件，第二行为原始文件，可用 `sha256sum -c --ignore-missing` 校验。
+pub fn write_sidecar(gz: &Path, original: &Path, digests: &Digests) -> io::Result<PathBuf> {
+    let path = sidecar_path(gz, digests.algorithm);
+    let text = format!(
+        "{}  {}\n{}  {}\n",
+        digests.compressed,
+        file_name(gz),
+        digests.uncompressed,
+        file_name(original)
+    );
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// 从旁路校验文件读取期望的摘要：文件名与 `gz` 相同的行为压缩摘要，另一行为原始内容摘要。
+pub fn read_sidecar(
+    sidecar: &Path,
+    gz: &Path,
+    algorithm: ChecksumAlgorithm,
+) -> io::Result<Digests> {
+    let text = fs::read_to_string(sidecar)?;
+    let invalid = |msg: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", sidecar.display(), msg),
+        )
+    };
+    let gz_name = file_name(gz);
+    let (mut compressed, mut uncompressed) = (None, None);
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let (digest, name) = line.split_once(' ').ok_or_else(|| invalid("格式错误"))?;
+        // `sha256sum -b` 以 `*` 标记二进制模式。
+        let name = name.trim_start_matches(' ').trim_start_matches('*');
+        let digest = digest.to_ascii_lowercase();
+        if name == gz_name {
+            compressed = Some(digest);
+        } else {
+            uncompressed = Some(digest);
+        }
+    }
+    match (compressed, uncompressed) {
+        (Some(compressed), Some(uncompressed)) => Ok(Digests {
+            algorithm,
+            compressed,
+            uncompressed,
+        }),
+        (None, _) => Err(invalid(&format!("缺少 {} 的摘要", gz_name))),
+        (_, None) => Err(invalid("缺少原始内容的摘要")),
+    }
+}
+
+/// 比较实际摘要与期望摘要，不一致时返回 `InvalidData`。
+pub fn verify_digests(expected: &Digests, actual: &Digests) -> io::Result<()> {
+    let check = |what: &str, want: &str, got: &str| {
+        if want == got {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} 校验失败（{}）：期望 {}，实际 {}",
+                    what, expected.algorithm, want, got
+                ),
+            ))
+        }
+    };
+    check("压缩数据", &expected.compressed, &actual.compressed)?;
+    check("解压内容", &expected.uncompressed, &actual.uncompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_of(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+        let mut h = algorithm.hasher();
+        h.update(data);
+        h.finalize_hex()
+    }
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            hex_of(ChecksumAlgorithm::Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(hex_of(ChecksumAlgorithm::Crc32, b"123456789"), "cbf43926");
+        assert_eq!(
+            hex_of(ChecksumAlgorithm::Blake3, b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!("BLAKE3".parse(), Ok(ChecksumAlgorithm::Blake3));
+        assert!("md5".parse::<ChecksumAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_sidecar_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("rgzip-sidecar-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let gz = dir.join("a.txt.gz");
+        let digests = Digests {
+            algorithm: ChecksumAlgorithm::Crc32,
+            compressed: "0011aabb".to_string(),
+            uncompressed: "ccddeeff".to_string(),
+        };
+        let path = write_sidecar(&gz, Path::new("a.txt"), &digests).unwrap();
+        assert_eq!(path, dir.join("a.txt.gz.crc32"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "0011aabb  a.txt.gz\nccddeeff  a.txt\n"
+        );
+        assert_eq!(
+            find_sidecar(&gz),
+            Some((path.clone(), ChecksumAlgorithm::Crc32))
+        );
+        let read = read_sidecar(&path, &gz, ChecksumAlgorithm::Crc32).unwrap();
+        assert_eq!(read, digests);
+
+        let mut bad = digests.clone();
+        bad.uncompressed = "00000000".to_string();
+        assert!(verify_digests(&digests, &bad).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
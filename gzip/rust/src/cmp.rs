@@ -0,0 +1,225 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use similar::TextDiff;
+
+/// 比较中提前结束的一侧。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// 两个 gzip 流按解压后内容比较的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOutcome {
+    /// 内容完全一致，`len` 为解压后的总字节数。
+    Identical { len: u64 },
+    /// 首个不同字节的解压后偏移（从 0 开始）及所在行号（从 1 开始）。
+    Differ { offset: u64, line: u64 },
+    /// 一侧在 `len` 字节后结束，另一侧仍有数据。
+    Eof { side: Side, len: u64 },
+}
+
+impl CmpOutcome {
+    pub fn is_identical(&self) -> bool {
+        matches!(self, CmpOutcome::Identical { .. })
+    }
+}
+
+/// 同步解压两个 gzip 流并逐字节比较，不产生临时文件；忽略头部差异（mtime、文件名等）。
+pub fn compare_gz_readers<A: Read, B: Read>(a: A, b: B) -> io::Result<CmpOutcome> {
+    let mut a = BufReader::new(MultiGzDecoder::new(a));
+    let mut b = BufReader::new(MultiGzDecoder::new(b));
+    let mut offset = 0u64;
+    let mut line = 1u64;
+
+    loop {
+        let buf_a = a.fill_buf()?;
+        let buf_b = b.fill_buf()?;
+        match (buf_a.is_empty(), buf_b.is_empty()) {
+            (true, true) => return Ok(CmpOutcome::Identical { len: offset }),
+            (true, false) => {
+                return Ok(CmpOutcome::Eof {
+                    side: Side::Left,
+                    len: offset,
+                })
+            }
+            (false, true) => {
+                return Ok(CmpOutcome::Eof {
+                    side: Side::Right,
+                    len: offset,
+                })
+            }
+            (false, false) => {}
+        }
+
+        let n = buf_a.len().min(buf_b.len());
+        let (chunk_a, chunk_b) = (&buf_a[..n], &buf_b[..n]);
+        if let Some(i) = chunk_a.iter().zip(chunk_b).position(|(x, y)| x != y) {
+            line += count_newlines(&chunk_a[..i]);
+            return Ok(CmpOutcome::Differ {
+                offset: offset + i as u64,
+                line,
+            });
+        }
+        line += count_newlines(chunk_a);
+        offset += n as u64;
+        a.consume(n);
+        b.consume(n);
+    }
+}
+
+/// 按解压后内容比较两个 gzip 文件。
+pub fn compare_gz_paths(a: &Path, b: &Path) -> io::Result<CmpOutcome> {
+    compare_gz_readers(File::open(a)?, File::open(b)?)
+}
+
+/// [`diff_gz_paths`] 单侧解压后内容的大小上限（16 MiB）：生成 diff 需要把两侧全部读入内存。
+pub const MAX_DIFF_SIZE: u64 = 16 << 20;
+
+/// 为两个 gzip 文件生成解压后内容的统一格式 diff；内容相同时返回空串。
+///
+/// 先流式比较，内容相同时不缓冲任何数据。任一侧不是 UTF-8 文本（或含 NUL），
+/// 或解压后超过 [`MAX_DIFF_SIZE`] 时返回 `None`，调用方应退回按偏移比较。
+pub fn diff_gz_paths(a: &Path, b: &Path, context: usize) -> io::Result<Option<String>> {
+    diff_gz_paths_limited(a, b, context, MAX_DIFF_SIZE)
+}
+
+fn diff_gz_paths_limited(
+    a: &Path,
+    b: &Path,
+    context: usize,
+    limit: u64,
+) -> io::Result<Option<String>> {
+    if compare_gz_paths(a, b)?.is_identical() {
+        return Ok(Some(String::new()));
+    }
+    let (Some(left), Some(right)) = (
+        read_gz_limited(File::open(a)?, limit)?,
+        read_gz_limited(File::open(b)?, limit)?,
+    ) else {
+        return Ok(None);
+    };
+    let (Some(left), Some(right)) = (as_text(&left), as_text(&right)) else {
+        return Ok(None);
+    };
+
+    let diff = TextDiff::from_lines(left, right);
+    let text = diff
+        .unified_diff()
+        .context_radius(context)
+        .header(&a.display().to_string(), &b.display().to_string())
+        .to_string();
+    Ok(Some(text))
+}
+
+/// 解压整个 gzip 流；解压后超过 `limit` 字节时返回 `None`，最多只缓冲 `limit + 1` 字节。
+fn read_gz_limited<R: Read>(reader: R, limit: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut out = Vec::new();
+    MultiGzDecoder::new(reader)
+        .take(limit + 1)
+        .read_to_end(&mut out)?;
+    Ok((out.len() as u64 <= limit).then_some(out))
+}
+
+fn as_text(data: &[u8]) -> Option<&str> {
+    if data.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(data).ok()
+}
+
+fn count_newlines(data: &[u8]) -> u64 {
+    data.iter().filter(|&&b| b == b'\n').count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress_reader_to_writer;
+    use flate2::{Compression, GzBuilder};
+    use std::io::Write;
+
+    fn gz(data: &[u8]) -> Vec<u8> {
+        compress_reader_to_writer(data, Vec::new(), 6).unwrap()
+    }
+
+    #[test]
+    fn test_identical_ignores_header() {
+        let mut named = GzBuilder::new()
+            .filename("a.txt")
+            .mtime(12345)
+            .write(Vec::new(), Compression::best());
+        named.write_all(b"hello\nworld\n").unwrap();
+        let named = named.finish().unwrap();
+
+        let outcome = compare_gz_readers(&named[..], &gz(b"hello\nworld\n")[..]).unwrap();
+        assert_eq!(outcome, CmpOutcome::Identical { len: 12 });
+    }
+
+    #[test]
+    fn test_first_difference() {
+        let a = gz(b"line1\nline2\nline3\n");
+        let b = gz(b"line1\nline2\nlinX3\n");
+        let outcome = compare_gz_readers(&a[..], &b[..]).unwrap();
+        assert_eq!(
+            outcome,
+            CmpOutcome::Differ {
+                offset: 15,
+                line: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_eof_on_shorter() {
+        let a = gz(b"abc");
+        let b = gz(b"abcdef");
+        let outcome = compare_gz_readers(&a[..], &b[..]).unwrap();
+        assert_eq!(
+            outcome,
+            CmpOutcome::Eof {
+                side: Side::Left,
+                len: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_multi_member_equals_single() {
+        let mut joined = gz(b"abc");
+        joined.extend(gz(b"def"));
+        let outcome = compare_gz_readers(&joined[..], &gz(b"abcdef")[..]).unwrap();
+        assert!(outcome.is_identical());
+    }
+
+    fn temp_gz(tag: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rgzip-cmp-{}-{}", tag, std::process::id()));
+        std::fs::write(&path, gz(data)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_diff_identical_text_and_limit() {
+        let a = temp_gz("diff-a", b"one\ntwo\nthree\n");
+        let b = temp_gz("diff-b", b"one\nTWO\nthree\n");
+        let same = temp_gz("diff-same", b"one\ntwo\nthree\n");
+
+        assert_eq!(
+            diff_gz_paths_limited(&a, &same, 3, 4).unwrap(),
+            Some(String::new())
+        );
+        let diff = diff_gz_paths_limited(&a, &b, 3, 64).unwrap().unwrap();
+        assert!(diff.contains("-two\n+TWO\n"), "{}", diff);
+        // 恰好等于上限仍可 diff，超过上限退回按偏移比较。
+        assert!(diff_gz_paths_limited(&a, &b, 3, 14).unwrap().is_some());
+        assert_eq!(diff_gz_paths_limited(&a, &b, 3, 13).unwrap(), None);
+
+        for path in [a, b, same] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// 配置文件中可设置的默认选项；未设置的项保持命令行默认值。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OptionDefaults {
+    pub level: Option<u32>,
+    pub keep: Option<bool>,
+    pub force: Option<bool>,
+    pub no_name: Option<bool>,
+    pub buffer_size: Option<String>,
+    pub mmap: Option<bool>,
+    pub reproducible: Option<bool>,
+}
+
+impl OptionDefaults {
+    /// 用 `other` 中已设置的项覆盖自身。
+    fn merge(&mut self, other: &OptionDefaults) {
+        if other.level.is_some() {
+            self.level = other.level;
+        }
+        if other.keep.is_some() {
+            self.keep = other.keep;
+        }
+        if other.force.is_some() {
+            self.force = other.force;
+        }
+        if other.no_name.is_some() {
+            self.no_name = other.no_name;
+        }
+        if other.buffer_size.is_some() {
+            self.buffer_size.clone_from(&other.buffer_size);
+        }
+        if other.mmap.is_some() {
+            self.mmap = other.mmap;
+        }
+        if other.reproducible.is_some() {
+            self.reproducible = other.reproducible;
+        }
+    }
+
+    /// 转换为等价的命令行参数；`keep` 按取值产生 `--keep` / `--no-keep`，其余布尔项为 `false` 时
+    /// 与内置默认值相同，不产生参数。
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(level) = self.level {
+            args.push(format!("--level={}", level));
+        }
+        if let Some(size) = &self.buffer_size {
+            args.push(format!("--buffer-size={}", size));
+        }
+        if let Some(keep) = self.keep {
+            args.push(if keep { "--keep" } else { "--no-keep" }.to_string());
+        }
+        for (flag, enabled) in [
+            ("--force", self.force),
+            ("--no-name", self.no_name),
+            ("--mmap", self.mmap),
+            ("--reproducible", self.reproducible),
+        ] {
+            if enabled == Some(true) {
+                args.push(flag.to_string());
+            }
+        }
+        args
+    }
+}
+
+/// 按输入文件后缀生效的规则。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "toml::Table")]
+pub struct SuffixRule {
+    pub suffix: String,
+    pub options: OptionDefaults,
+}
+
+// 不使用 `#[serde(flatten)]`：它会让 `deny_unknown_fields` 失效，拼错的键将被静默忽略。
+impl TryFrom<toml::Table> for SuffixRule {
+    type Error = String;
+
+    fn try_from(mut table: toml::Table) -> Result<Self, Self::Error> {
+        let suffix = match table.remove("suffix") {
+            Some(toml::Value::String(s)) if !s.is_empty() => s,
+            _ => return Err("rules 中的每一项都需要非空的 suffix".to_string()),
+        };
+        let options = OptionDefaults::deserialize(table).map_err(|e| e.to_string())?;
+        Ok(SuffixRule { suffix, options })
+    }
+}
+
+/// `rgzip.toml` 配置文件。
+///
+/// ```toml
+/// [defaults]
+/// level = 9
+/// no_name = true
+///
+/// [[rules]]
+/// suffix = ".log"
+/// level = 1
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: OptionDefaults,
+    #[serde(default)]
+    pub rules: Vec<SuffixRule>,
+}
+
+impl Config {
+    pub fn from_toml_str(text: &str) -> Result<Config, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// 读取配置文件；文件不存在时返回空配置。
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                Config::from_toml_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// 为指定输入生成参数：先应用 `[defaults]`，再依次应用后缀匹配的 `[[rules]]`（后者优先）。
+    pub fn args_for(&self, input: Option<&Path>) -> Vec<String> {
+        let mut merged = self.defaults.clone();
+        let name = input
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+        for rule in &self.rules {
+            if !name.is_empty() && name.ends_with(&rule.suffix) {
+                merged.merge(&rule.options);
+            }
+        }
+        merged.to_args()
+    }
+}
+
+/// 默认配置文件路径：`$RGZIP_CONFIG`，否则 `$XDG_CONFIG_HOME/rgzip.toml`，否则 `~/.config/rgzip.toml`。
+pub fn default_config_path() -> Option<PathBuf> {
+    if let Some(p) = env::var_os("RGZIP_CONFIG") {
+        return Some(PathBuf::from(p));
+    }
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("rgzip.toml"));
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("rgzip.toml"))
+}
+
+/// 拆分 `GZIP` / `RGZIP` 环境变量中的默认选项（按空白分隔）。
+///
+/// 与 GNU gzip 一致，环境变量中只允许出现选项；选项值需写成 `--level=9` 或 `-l9` 形式，
+/// 以免误把文件名当作输入。`-1` 至 `-9` 会被展开为 `--level=N`。
+pub fn split_env_options(name: &str, value: &str) -> Result<Vec<String>, String> {
+    value
+        .split_whitespace()
+        .map(|tok| {
+            if !tok.starts_with('-') || tok == "-" || tok == "--" {
+                return Err(format!("环境变量 {} 中只能包含选项: {}", name, tok));
+            }
+            Ok(expand_level_shorthand(tok))
+        })
+        .collect()
+}
+
+/// 将 GNU 风格的 `-1` … `-9`（以及 `-0`）展开为 `--level=N`，其他参数原样返回。
+pub fn expand_level_shorthand(arg: &str) -> String {
+    let bytes = arg.as_bytes();
+    if bytes.len() == 2 && bytes[0] == b'-' && bytes[1].is_ascii_digit() {
+        return format!("--level={}", bytes[1] as char);
+    }
+    arg.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+[defaults]
+level = 9
+no_name = true
+
+[[rules]]
+suffix = ".log"
+level = 1
+keep = true
+
+[[rules]]
+suffix = ".app.log"
+level = 3
+keep = false
+"#;
+
+    #[test]
+    fn test_rules_override_defaults() {
+        let cfg = Config::from_toml_str(SAMPLE).unwrap();
+        assert_eq!(
+            cfg.args_for(Some(Path::new("data.json"))),
+            ["--level=9", "--no-name"]
+        );
+        assert_eq!(
+            cfg.args_for(Some(Path::new("/var/x.log"))),
+            ["--level=1", "--keep", "--no-name"]
+        );
+        assert_eq!(
+            cfg.args_for(Some(Path::new("svc.app.log"))),
+            ["--level=3", "--no-keep", "--no-name"]
+        );
+        assert_eq!(cfg.args_for(None), ["--level=9", "--no-name"]);
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        assert!(Config::from_toml_str("[defaults]\nlevle = 3\n").is_err());
+        assert!(Config::from_toml_str("[[rules]]\nsuffix = \".x\"\nlevle = 3\n").is_err());
+    }
+
+    #[test]
+    fn test_split_env_options() {
+        assert_eq!(
+            split_env_options("GZIP", " -9  -n ").unwrap(),
+            ["--level=9", "-n"]
+        );
+        assert!(split_env_options("GZIP", "-l 9").is_err());
+        assert!(split_env_options("GZIP", "file.txt").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+use flate2::write::ZlibEncoder;
+use flate2::{Compress, Compression, Decompress, FlushDecompress, Status};
+
+use crate::sanitize_level;
+
+/// deflate 窗口大小；更长的字典只有末尾 32 KiB 会被引用。
+pub const MAX_DICT_SIZE: usize = 32 * 1024;
+
+/// 字典训练时统计的子串长度（也是候选片段的最短长度）。
+const GRAM: usize = 8;
+
+/// 使用预设字典的压缩输出为 zlib 流（RFC 1950，头部置 FDICT 并记录字典的 Adler-32），
+/// 因 gzip 格式本身不支持预设字典。约定文件后缀为 `.zz`。
+pub const DICT_SUFFIX: &str = ".zz";
+
+fn window(dict: &[u8]) -> &[u8] {
+    &dict[dict.len().saturating_sub(MAX_DICT_SIZE)..]
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// 创建以 `dict` 为预设字典的 zlib 编码器。
+pub(crate) fn dict_encoder<W: Write>(
+    writer: W,
+    level: u32,
+    dict: &[u8],
+) -> io::Result<ZlibEncoder<W>> {
+    let mut compress = Compress::new(Compression::new(sanitize_level(level)), true);
+    compress
+        .set_dictionary(window(dict))
+        .map_err(io::Error::other)?;
+    Ok(ZlibEncoder::new_with_compress(writer, compress))
+}
+
+/// 解压以预设字典压缩的 zlib 流；字典与压缩时不一致时返回 `InvalidData`。
+pub(crate) fn decompress_with_dict<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    dict: &[u8],
+    buffer_size: usize,
+) -> io::Result<()> {
+    let mut decoder = Decompress::new(true);
+    let mut out = vec![0u8; buffer_size.max(1)];
+    loop {
+        let input = reader.fill_buf()?;
+        let eof = input.is_empty();
+        let flush = if eof {
+            FlushDecompress::Finish
+        } else {
+            FlushDecompress::None
+        };
+        let (total_in, total_out) = (decoder.total_in(), decoder.total_out());
+        let result = decoder.decompress(input, &mut out, flush);
+        let consumed = (decoder.total_in() - total_in) as usize;
+        let produced = (decoder.total_out() - total_out) as usize;
+        reader.consume(consumed);
+        writer.write_all(&out[..produced])?;
+        match result {
+            Ok(Status::StreamEnd) => break,
+            Ok(_) if eof && produced == 0 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "zlib 流不完整",
+                ))
+            }
+            Ok(_) => {}
+            Err(e) if e.needs_dictionary().is_some() => {
+                decoder
+                    .set_dictionary(window(dict))
+                    .map_err(|_| invalid_data("字典与压缩时使用的不一致"))?;
+            }
+            Err(e) => return Err(invalid_data(e.to_string())),
+        }
+    }
+    if !reader.fill_buf()?.is_empty() {
+        return Err(invalid_data("zlib 流之后存在多余数据"));
+    }
+    writer.flush()
+}
+
+/// 从样本中提取公共子串构建预设字典，长度不超过 `max_size`（最多 [`MAX_DICT_SIZE`]）。
+///
+/// 统计每个 8 字节子串出现在多少个样本中，把样本里连续命中公共子串的区间作为候选片段，
+/// 按“覆盖样本数之和”排序后贪心选取；得分最高的片段放在字典末尾，离待压缩数据最近。
+pub fn train_dictionary<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> Vec<u8> {
+    let max_size = max_size.min(MAX_DICT_SIZE);
+    let threshold = samples.len().min(2) as u32;
+
+    let mut doc_freq: HashMap<&[u8], u32> = HashMap::new();
+    for sample in samples {
+        let grams: HashSet<&[u8]> = sample.as_ref().windows(GRAM).collect();
+        for gram in grams {
+            *doc_freq.entry(gram).or_default() += 1;
+        }
+    }
+
+    let mut candidates: HashMap<&[u8], u64> = HashMap::new();
+    for sample in samples {
+        let sample = sample.as_ref();
+        let mut run: Option<(usize, u64)> = None;
+        for (i, gram) in sample.windows(GRAM).enumerate() {
+            let freq = doc_freq[gram];
+            match (&mut run, freq >= threshold) {
+                (Some((_, score)), true) => *score += u64::from(freq),
+                (None, true) => run = Some((i, u64::from(freq))),
+                (Some((start, score)), false) => {
+                    let piece = &sample[*start..i - 1 + GRAM];
+                    let best = candidates.entry(piece).or_default();
+                    *best = (*best).max(*score);
+                    run = None;
+                }
+                (None, false) => {}
+            }
+        }
+        if let Some((start, score)) = run {
+            let best = candidates.entry(&sample[start..]).or_default();
+            *best = (*best).max(score);
+        }
+    }
+
+    let mut ranked: Vec<(&[u8], u64)> = candidates.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut chosen: Vec<&[u8]> = Vec::new();
+    let mut size = 0;
+    for (piece, _) in ranked {
+        if size + piece.len() > max_size {
+            continue;
+        }
+        if chosen
+            .iter()
+            .any(|c| c.windows(piece.len()).any(|w| w == piece))
+        {
+            continue;
+        }
+        size += piece.len();
+        chosen.push(piece);
+        if max_size - size < GRAM {
+            break;
+        }
+    }
+    chosen
+        .iter()
+        .rev()
+        .flat_map(|c| c.iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compress_reader_to_writer, compress_reader_to_writer_with_options};
+    use crate::{decompress_reader_to_writer_with_options, CompressOptions, DecompressOptions};
+
+    fn record(i: u32) -> Vec<u8> {
+        format!(
+            r#"{{"id":{},"user":{{"name":"user-{}","email":"user{}@example.com","active":{}}},"tags":["alpha","beta"],"created_at":"2026-10-{:02}T12:{:02}:00Z"}}"#,
+            i,
+            i * 7 % 1000,
+            i,
+            i.is_multiple_of(3),
+            i % 28 + 1,
+            i % 60
+        )
+        .into_bytes()
+    }
+
+    fn with_dict(dict: &[u8]) -> (CompressOptions, DecompressOptions) {
+        let copts = CompressOptions {
+            level: 9,
+            dict: Some(dict.to_vec()),
+            ..Default::default()
+        };
+        let dopts = DecompressOptions {
+            dict: Some(dict.to_vec()),
+            ..Default::default()
+        };
+        (copts, dopts)
+    }
+
+    #[test]
+    fn test_dict_improves_small_records() {
+        let training: Vec<Vec<u8>> = (0..200).map(record).collect();
+        let dict = train_dictionary(&training, MAX_DICT_SIZE);
+        assert!(!dict.is_empty() && dict.len() <= MAX_DICT_SIZE);
+        let (copts, dopts) = with_dict(&dict);
+
+        let (mut raw, mut plain, mut primed) = (0, 0, 0);
+        for i in 1000..1100 {
+            let data = record(i);
+            raw += data.len();
+            plain += compress_reader_to_writer(&data[..], Vec::new(), 9)
+                .unwrap()
+                .len();
+            let zz = compress_reader_to_writer_with_options(&data[..], Vec::new(), &copts).unwrap();
+            primed += zz.len();
+
+            let mut out = Vec::new();
+            decompress_reader_to_writer_with_options(&zz[..], &mut out, &dopts).unwrap();
+            assert_eq!(out, data);
+        }
+        // 字典压缩应比逐条 gzip 至少缩小一半，且小于原文。
+        assert!(
+            primed * 2 < plain,
+            "字典 {} 字节, gzip {} 字节",
+            primed,
+            plain
+        );
+        assert!(primed < raw, "字典 {} 字节, 原始 {} 字节", primed, raw);
+    }
+
+    #[test]
+    fn test_wrong_or_missing_dict_rejected() {
+        let (copts, _) = with_dict(b"\"user\":{\"name\":\"user-");
+        let zz =
+            compress_reader_to_writer_with_options(&record(1)[..], Vec::new(), &copts).unwrap();
+        assert_eq!(zz[1] & 0x20, 0x20, "zlib 头应设置 FDICT");
+
+        let (_, other) = with_dict(b"unrelated dictionary");
+        let err =
+            decompress_reader_to_writer_with_options(&zz[..], Vec::new(), &other).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let (_, dopts) = with_dict(b"\"user\":{\"name\":\"user-");
+        let err = decompress_reader_to_writer_with_options(&zz[..zz.len() - 2], Vec::new(), &dopts)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_train_is_deterministic_and_bounded() {
+        let samples: Vec<Vec<u8>> = (0..50).map(record).collect();
+        let a = train_dictionary(&samples, 256);
+        assert!(a.len() <= 256);
+        assert_eq!(a, train_dictionary(&samples, 256));
+        assert!(train_dictionary(&[b"only one sample"], 1024).len() <= 1024);
+        assert!(train_dictionary::<&[u8]>(&[], 1024).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::{self, Read};
+
+const FTEXT: u8 = 0x01;
+const FHCRC: u8 = 0x02;
+const FEXTRA: u8 = 0x04;
+const FNAME: u8 = 0x08;
+const FCOMMENT: u8 = 0x10;
+const RESERVED: u8 = 0xe0;
+/// 文件名、注释字段的长度上限，防止畸形输入耗尽内存。
+const MAX_FIELD_LEN: usize = 64 * 1024;
+
+/// gzip 成员头（RFC 1952）。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GzHeader {
+    pub flags: u8,
+    pub mtime: u32,
+    pub xfl: u8,
+    pub os: u8,
+    pub extra: Option<Vec<u8>>,
+    pub filename: Option<Vec<u8>>,
+    pub comment: Option<Vec<u8>>,
+    /// 头部 CRC16（仅在设置 FHCRC 时存在）。
+    pub header_crc: Option<u16>,
+    /// 头部总字节数。
+    pub len: usize,
+}
+
+impl GzHeader {
+    pub fn is_text(&self) -> bool {
+        self.flags & FTEXT != 0
+    }
+
+    /// 以 UTF-8（有损）返回头中记录的原文件名。
+    pub fn filename_lossy(&self) -> Option<String> {
+        self.filename
+            .as_ref()
+            .map(|n| String::from_utf8_lossy(n).into_owned())
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn read_zero_terminated<R: Read>(reader: &mut R, len: &mut usize) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        *len += 1;
+        if byte[0] == 0 {
+            return Ok(out);
+        }
+        if out.len() >= MAX_FIELD_LEN {
+            return Err(invalid("gzip 头字段过长"));
+        }
+        out.push(byte[0]);
+    }
+}
+
+/// 从流的当前位置读取并校验一个 gzip 成员头，读取器停在压缩数据起始处。
+///
+/// 魔数、压缩方法或保留标志位不合法时返回 `InvalidData`；数据不足时返回 `UnexpectedEof`。
+pub fn read_gz_header<R: Read>(mut reader: R) -> io::Result<GzHeader> {
+    let mut fixed = [0u8; 10];
+    reader.read_exact(&mut fixed)?;
+    if fixed[0] != 0x1f || fixed[1] != 0x8b {
+        return Err(invalid("不是 gzip 格式（魔数不匹配）"));
+    }
+    if fixed[2] != 8 {
+        return Err(invalid("不支持的压缩方法（仅支持 deflate）"));
+    }
+    let flags = fixed[3];
+    if flags & RESERVED != 0 {
+        return Err(invalid("gzip 头包含保留标志位"));
+    }
+
+    let mut header = GzHeader {
+        flags,
+        mtime: u32::from_le_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]),
+        xfl: fixed[8],
+        os: fixed[9],
+        len: fixed.len(),
+        ..Default::default()
+    };
+
+    if flags & FEXTRA != 0 {
+        let mut xlen = [0u8; 2];
+        reader.read_exact(&mut xlen)?;
+        let mut extra = vec![0u8; u16::from_le_bytes(xlen) as usize];
+        reader.read_exact(&mut extra)?;
+        header.len += 2 + extra.len();
+        header.extra = Some(extra);
+    }
+    if flags & FNAME != 0 {
+        header.filename = Some(read_zero_terminated(&mut reader, &mut header.len)?);
+    }
+    if flags & FCOMMENT != 0 {
+        header.comment = Some(read_zero_terminated(&mut reader, &mut header.len)?);
+    }
+    if flags & FHCRC != 0 {
+        let mut crc = [0u8; 2];
+        reader.read_exact(&mut crc)?;
+        header.len += 2;
+        header.header_crc = Some(u16::from_le_bytes(crc));
+    }
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression, GzBuilder};
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_builder_header() {
+        let mut enc = GzBuilder::new()
+            .filename("data.csv")
+            .comment("note")
+            .extra(vec![1, 2, 3])
+            .mtime(0x01020304)
+            .write(Vec::new(), Compression::best());
+        enc.write_all(b"payload").unwrap();
+        let gz = enc.finish().unwrap();
+
+        let h = read_gz_header(&gz[..]).unwrap();
+        assert_eq!(h.mtime, 0x01020304);
+        assert_eq!(h.filename_lossy().as_deref(), Some("data.csv"));
+        assert_eq!(h.comment.as_deref(), Some(&b"note"[..]));
+        assert_eq!(h.extra.as_deref(), Some(&[1u8, 2, 3][..]));
+        assert_eq!(h.len, 10 + 2 + 3 + 9 + 5);
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let kind = |data: &[u8]| read_gz_header(data).unwrap_err().kind();
+        assert_eq!(kind(b""), io::ErrorKind::UnexpectedEof);
+        assert_eq!(kind(b"PK\x03\x04\0\0\0\0\0\0"), io::ErrorKind::InvalidData);
+        assert_eq!(
+            kind(b"\x1f\x8b\x07\0\0\0\0\0\0\x03"),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            kind(b"\x1f\x8b\x08\x20\0\0\0\0\0\x03"),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            kind(b"\x1f\x8b\x08\x08\0\0\0\0\0\x03abc"),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -1,11 +1,51 @@
+use std::cell::Cell;
 use std::fs::{self, File};
-use std::io::{self, Read, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::time::UNIX_EPOCH;
 
-use flate2::read::GzDecoder;
+use checksum::{HashingReader, HashingWriter};
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::{GzEncoder, ZlibEncoder};
 use flate2::{Compression, GzBuilder};
 
+#[cfg(feature = "async")]
+mod async_io;
+mod checksum;
+mod cmp;
+mod config;
+mod dict;
+mod header;
+mod limit;
+mod rotate;
+mod split;
+mod tar;
+
+#[cfg(feature = "async")]
+pub use async_io::{compress_reader_to_writer_async, decompress_reader_to_writer_async};
+
+pub use checksum::{
+    find_sidecar, read_sidecar, sidecar_path, verify_digests, write_sidecar, ChecksumAlgorithm,
+    Digests, Hasher,
+};
+pub use cmp::{
+    compare_gz_paths, compare_gz_readers, diff_gz_paths, CmpOutcome, Side, MAX_DIFF_SIZE,
+};
+pub use config::{
+    default_config_path, expand_level_shorthand, split_env_options, Config, OptionDefaults,
+    SuffixRule,
+};
+pub use dict::{train_dictionary, DICT_SUFFIX, MAX_DICT_SIZE};
+pub use header::{read_gz_header, GzHeader};
+pub use limit::{parse_size, CountingReader, DecompressLimits, LimitExceeded, LimitedWriter};
+pub use rotate::{parse_age, rotate_dir, RotateEntry, RotateOptions, RotateOutcome, RotateReport};
+pub use split::{
+    compress_path_split, compress_reader_split, decompress_volumes_to_path, find_volumes,
+    remove_stale_volumes, volume_path, VolumeReader, MIN_SPLIT_SIZE,
+};
+pub use tar::{extract_tar_gz, list_tar_gz, safe_join, EntryKind, TarEntry, TarSink, TarVisitor};
+
 pub const MIN_LEVEL: u32 = 0;
 pub const MAX_LEVEL: u32 = 9;
 
@@ -14,72 +54,620 @@ pub fn sanitize_level(level: u32) -> u32 {
     level.clamp(MIN_LEVEL, MAX_LEVEL)
 }
 
+/// gzip 头中表示“未知”操作系统的 OS 字节，可复现模式下使用。
+pub const OS_UNKNOWN: u8 = 255;
+
+/// 当前平台对应的 gzip 头 OS 字节（RFC 1952）：Unix 类系统为 3，Windows 为 11（NTFS）。
+pub const HOST_OS: u8 = if cfg!(windows) {
+    11
+} else if cfg!(unix) {
+    3
+} else {
+    OS_UNKNOWN
+};
+
+/// 写入 gzip 头的元数据。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HeaderInfo {
+    pub(crate) filename: Option<String>,
+    pub(crate) mtime: u32,
+    pub(crate) os: u8,
+}
+
+impl Default for HeaderInfo {
+    fn default() -> Self {
+        HeaderInfo {
+            filename: None,
+            mtime: 0,
+            os: HOST_OS,
+        }
+    }
+}
+
+/// 创建 gzip 编码器；同步与异步接口共用同一套头部构造逻辑。
+pub(crate) fn gz_encoder<W: Write>(writer: W, level: u32, header: &HeaderInfo) -> GzEncoder<W> {
+    let mut builder = GzBuilder::new()
+        .mtime(header.mtime)
+        .operating_system(header.os);
+    if let Some(name) = &header.filename {
+        builder = builder.filename(name.as_str());
+    }
+    builder.write(writer, Compression::new(sanitize_level(level)))
+}
+
+/// 统一解码错误的类型：flate2 将损坏的输入报告为 `InvalidInput`，这里改为 `InvalidData`，
+/// 与 [`read_gz_header`] 一致；同步与异步接口共用。
+pub(crate) fn corrupt_to_invalid_data(e: io::Error) -> io::Error {
+    if e.kind() == io::ErrorKind::InvalidInput {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    } else {
+        e
+    }
+}
+
+/// 推送式 gzip 解码器：调用方分块送入压缩数据，再用定长缓冲区取出解压数据。
+///
+/// 同步与异步接口都经由它解码，因此多成员（multi-member）处理与错误类型完全一致：
+/// 输入截断为 `UnexpectedEof`，格式或校验错误为 `InvalidData`。每次取出的数据不超过
+/// 调用方的缓冲区，内存占用与输入的压缩比无关。
+pub struct GzPushDecoder(MultiGzDecoder<PushInput>);
+
+/// 解码器的输入队列：已送入的数据取尽而输入尚未结束时返回 `WouldBlock`，
+/// flate2 会保留解码状态，待送入下一块后继续。
+#[derive(Default)]
+struct PushInput {
+    buf: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl Read for PushInput {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let avail = self.fill_buf()?;
+        let n = avail.len().min(out.len());
+        out[..n].copy_from_slice(&avail[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for PushInput {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.buf.len() && !self.finished {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+impl Default for GzPushDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GzPushDecoder {
+    pub fn new() -> Self {
+        GzPushDecoder(MultiGzDecoder::new(PushInput::default()))
+    }
+
+    /// 送入一块压缩数据，排在尚未解码的输入之后。通常在 [`read`](Self::read) 返回 `None` 后调用；
+    /// 提前送入的数据会暂存在解码器中。
+    pub fn push(&mut self, input: &[u8]) {
+        let queue = self.0.get_mut();
+        queue.buf.drain(..queue.pos);
+        queue.pos = 0;
+        queue.buf.extend_from_slice(input);
+    }
+
+    /// 标记输入结束，此后数据不完整将报告为 `UnexpectedEof`。
+    pub fn finish(&mut self) {
+        self.0.get_mut().finished = true;
+    }
+
+    /// 取出解压数据：`Some(0)` 表示流已结束，`None` 表示需要送入更多输入。
+    /// `out` 不能为空。
+    pub fn read(&mut self, out: &mut [u8]) -> io::Result<Option<usize>> {
+        debug_assert!(!out.is_empty());
+        match self.0.read(out) {
+            Ok(n) => Ok(Some(n)),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(corrupt_to_invalid_data(e)),
+        }
+    }
+}
+
+/// 按压缩参数选择的编码器：默认 gzip，设置预设字典时为 zlib。
+enum StreamEncoder<W: Write> {
+    Gzip(GzEncoder<W>),
+    Zlib(ZlibEncoder<W>),
+}
+
+impl<W: Write> StreamEncoder<W> {
+    fn new(writer: W, opts: &CompressOptions, header: &HeaderInfo) -> io::Result<Self> {
+        Ok(match &opts.dict {
+            Some(dict) => StreamEncoder::Zlib(dict::dict_encoder(writer, opts.level, dict)?),
+            None => StreamEncoder::Gzip(gz_encoder(writer, opts.level, header)),
+        })
+    }
+
+    fn finish(self) -> io::Result<W> {
+        match self {
+            StreamEncoder::Gzip(e) => e.finish(),
+            StreamEncoder::Zlib(e) => e.finish(),
+        }
+    }
+}
+
+/// 将输入送入编码器。可复现模式下每次读满固定大小的块再写入，
+/// 使压缩结果与读取器的短读（如管道）和缓冲区配置无关。
+fn feed_encoder<R: Read, W: Write>(
+    mut reader: R,
+    encoder: &mut StreamEncoder<W>,
+    opts: &CompressOptions,
+) -> io::Result<()> {
+    if opts.reproducible.is_none() {
+        return copy_with_buffer(reader, encoder, opts.buffer_size).map(drop);
+    }
+    let mut buf = vec![0u8; DEFAULT_BUFFER_SIZE];
+    loop {
+        let filled = read_full(&mut reader, &mut buf)?;
+        encoder.write_all(&buf[..filled])?;
+        if filled < buf.len() {
+            return Ok(());
+        }
+    }
+}
+
+/// 读满 `buf`（输入结束时除外），返回读取的字节数。
+pub(crate) fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+impl<W: Write> Write for StreamEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            StreamEncoder::Gzip(e) => e.write(buf),
+            StreamEncoder::Zlib(e) => e.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            StreamEncoder::Gzip(e) => e.flush(),
+            StreamEncoder::Zlib(e) => e.flush(),
+        }
+    }
+}
+
+/// 默认读写缓冲区大小；`io::copy` 的 8 KiB 在高速存储上会成为瓶颈。
+pub const DEFAULT_BUFFER_SIZE: usize = 128 * 1024;
+
+/// 压缩参数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressOptions {
+    /// 压缩级别（0-9）。
+    pub level: u32,
+    /// 读写缓冲区大小（字节）。
+    pub buffer_size: usize,
+    /// 对普通文件输入使用内存映射（仅 [`compress_path_with_options`] 生效）。
+    pub mmap: bool,
+    /// 不在 gzip 头中记录原文件名与修改时间（同 `gzip -n`）。
+    pub no_name: bool,
+    /// 预设字典；设置后输出为 zlib 流（见 [`DICT_SUFFIX`]），不再写 gzip 头。
+    pub dict: Option<Vec<u8>>,
+    /// 可复现输出：`Some(epoch)` 时 mtime 取 `min(文件 mtime, epoch)`，OS 字节固定为
+    /// [`OS_UNKNOWN`]，头部只可能带 FNAME 标志，并以固定分块送入编码器
+    /// （忽略 `buffer_size` 与 `mmap`），使相同内容在任何机器上得到相同字节。
+    pub reproducible: Option<u32>,
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        CompressOptions {
+            level: 6,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            mmap: false,
+            no_name: false,
+            dict: None,
+            reproducible: None,
+        }
+    }
+}
+
+impl CompressOptions {
+    pub fn with_level(level: u32) -> Self {
+        CompressOptions {
+            level,
+            ..Default::default()
+        }
+    }
+}
+
+/// 解压参数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecompressOptions {
+    /// 读写缓冲区大小（字节）。
+    pub buffer_size: usize,
+    /// 输出限制，见 [`DecompressLimits`]。
+    pub limits: DecompressLimits,
+    /// 预设字典；设置后输入按 zlib 流解码，须与压缩时的字典一致。
+    pub dict: Option<Vec<u8>>,
+}
+
+impl Default for DecompressOptions {
+    fn default() -> Self {
+        DecompressOptions {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            limits: DecompressLimits::default(),
+            dict: None,
+        }
+    }
+}
+
+/// 使用指定大小的缓冲区在读取器与写入器之间复制数据，返回复制的字节数。
+pub fn copy_with_buffer<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    buffer_size: usize,
+) -> io::Result<u64> {
+    let mut buf = vec![0u8; buffer_size.max(1)];
+    let mut total = 0u64;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(total),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        total += n as u64;
+    }
+}
+
 /// 将指定文件压缩为 gzip 文件。
 pub fn compress_path(input: &Path, output: &Path, level: u32) -> io::Result<()> {
-    let level = sanitize_level(level);
-    let mut in_file = File::open(input)?;
+    compress_path_with_options(input, output, &CompressOptions::with_level(level))
+}
 
-    let meta = in_file.metadata().ok();
+/// 按 `opts` 将指定文件压缩为 gzip 文件；启用 `mmap` 且输入为非空普通文件时走内存映射路径。
+pub fn compress_path_with_options(
+    input: &Path,
+    output: &Path,
+    opts: &CompressOptions,
+) -> io::Result<()> {
+    compress_file(input, output, opts, None).map(drop)
+}
+
+/// 同 [`compress_path_with_options`]，并在同一遍读写中计算原始内容与压缩输出的摘要。
+pub fn compress_path_with_checksum(
+    input: &Path,
+    output: &Path,
+    opts: &CompressOptions,
+    algorithm: ChecksumAlgorithm,
+) -> io::Result<Digests> {
+    compress_file(input, output, opts, Some(algorithm))
+        .map(|d| d.expect("设置了算法时总会返回摘要"))
+}
+
+/// 按压缩参数为文件输入构造 gzip 头：记录文件名与 mtime（`no_name` 时省略），
+/// 可复现模式下截断 mtime 并固定 OS 字节。
+pub(crate) fn file_header(
+    input: &Path,
+    meta: Option<&fs::Metadata>,
+    opts: &CompressOptions,
+) -> HeaderInfo {
     let mtime = meta
         .and_then(|m| m.modified().ok())
         .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
         .map(|d| d.as_secs() as u32)
         .unwrap_or(0);
+    let mut header = if opts.no_name {
+        HeaderInfo::default()
+    } else {
+        HeaderInfo {
+            filename: input
+                .file_name()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string()),
+            mtime,
+            ..Default::default()
+        }
+    };
+    if let Some(epoch) = opts.reproducible {
+        header.mtime = header.mtime.min(epoch);
+        header.os = OS_UNKNOWN;
+    }
+    header
+}
 
-    let out_file = File::create(output)?;
+/// 从任意读取器压缩时使用的 gzip 头（不含文件名与 mtime）。
+pub(crate) fn stream_header(opts: &CompressOptions) -> HeaderInfo {
+    let mut header = HeaderInfo::default();
+    if opts.reproducible.is_some() {
+        header.os = OS_UNKNOWN;
+    }
+    header
+}
+
+fn compress_file(
+    input: &Path,
+    output: &Path,
+    opts: &CompressOptions,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> io::Result<Option<Digests>> {
+    let in_file = File::open(input)?;
 
-    let filename_in_header = input
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_string();
+    let meta = in_file.metadata().ok();
+    let header = file_header(input, meta.as_ref(), opts);
 
-    let mut encoder = GzBuilder::new()
-        .mtime(mtime)
-        .filename(filename_in_header)
-        .write(out_file, Compression::new(level));
+    let out_file = HashingWriter::new(
+        BufWriter::with_capacity(opts.buffer_size.max(1), File::create(output)?),
+        algorithm,
+    );
+    let mut encoder = StreamEncoder::new(out_file, opts, &header)?;
 
-    io::copy(&mut in_file, &mut encoder)?;
-    let _ = encoder.finish()?;
-    Ok(())
+    let use_mmap = opts.mmap
+        && opts.reproducible.is_none()
+        && meta.is_some_and(|m| m.is_file() && m.len() > 0);
+    let uncompressed = if use_mmap {
+        let map = map_file(&in_file)?;
+        let mut hasher = HashingWriter::new(io::sink(), algorithm);
+        hasher.update(&map);
+        encoder.write_all(&map)?;
+        hasher.finalize_hex()
+    } else {
+        let mut reader = HashingReader::new(in_file, algorithm);
+        feed_encoder(&mut reader, &mut encoder, opts)?;
+        reader.finalize_hex()
+    };
+    let mut out_file = encoder.finish()?;
+    out_file.flush()?;
+    Ok(algorithm
+        .zip(out_file.finalize_hex())
+        .zip(uncompressed)
+        .map(|((algorithm, compressed), uncompressed)| Digests {
+            algorithm,
+            compressed,
+            uncompressed,
+        }))
+}
+
+/// 以只读方式映射整个文件。
+fn map_file(file: &File) -> io::Result<memmap2::Mmap> {
+    // SAFETY: 映射为只读；若其他进程在压缩期间截断或修改该文件，读取结果未定义，
+    // 因此仅在调用方显式启用 `mmap` 时使用。
+    unsafe { memmap2::Mmap::map(file) }
 }
 
 /// 将任意读取器的内容压缩到指定文件。
 pub fn compress_reader_to_path<R: Read>(reader: R, output: &Path, level: u32) -> io::Result<()> {
-    let file = File::create(output)?;
-    let _ = compress_reader_to_writer(reader, file, level)?;
-    Ok(())
+    compress_reader_to_path_with_options(reader, output, &CompressOptions::with_level(level))
+}
+
+/// 按 `opts` 将任意读取器的内容压缩到指定文件。
+pub fn compress_reader_to_path_with_options<R: Read>(
+    reader: R,
+    output: &Path,
+    opts: &CompressOptions,
+) -> io::Result<()> {
+    let file = BufWriter::with_capacity(opts.buffer_size.max(1), File::create(output)?);
+    compress_reader_to_writer_with_options(reader, file, opts)?.flush()
+}
+
+/// 按 `opts` 创建向 `writer` 写出 gzip 流的编码器，头部与
+/// [`compress_reader_to_writer_with_options`] 一致（不含文件名与 mtime，可复现模式下固定 OS 字节）。
+/// 设置了 `dict` 时返回 `InvalidInput`：字典压缩的输出是 zlib 流而不是 gzip。
+pub fn stream_encoder<W: Write>(writer: W, opts: &CompressOptions) -> io::Result<GzEncoder<W>> {
+    if opts.dict.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "流式 gzip 编码器不支持预设字典",
+        ));
+    }
+    Ok(gz_encoder(writer, opts.level, &stream_header(opts)))
 }
 
 /// 将任意读取器的内容压缩到任意写入器。
 pub fn compress_reader_to_writer<R: Read, W: Write>(
-    mut reader: R,
+    reader: R,
     writer: W,
     level: u32,
 ) -> io::Result<W> {
-    let level = sanitize_level(level);
-    let mut encoder = GzBuilder::new().write(writer, Compression::new(level));
-    io::copy(&mut reader, &mut encoder)?;
+    compress_reader_to_writer_with_options(reader, writer, &CompressOptions::with_level(level))
+}
+
+/// 按 `opts` 将任意读取器的内容压缩到任意写入器。
+pub fn compress_reader_to_writer_with_options<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    opts: &CompressOptions,
+) -> io::Result<W> {
+    let mut encoder = StreamEncoder::new(writer, opts, &stream_header(opts))?;
+    feed_encoder(reader, &mut encoder, opts)?;
     encoder.finish()
 }
 
 /// 将 gzip 文件解压到指定文件。
 pub fn decompress_path(input: &Path, output: &Path) -> io::Result<()> {
+    decompress_path_with_options(input, output, &DecompressOptions::default())
+}
+
+/// 将 gzip 文件解压到指定文件，并施加输出限制；失败时删除不完整的输出文件。
+pub fn decompress_path_with_limits(
+    input: &Path,
+    output: &Path,
+    limits: DecompressLimits,
+) -> io::Result<()> {
+    let opts = DecompressOptions {
+        limits,
+        ..Default::default()
+    };
+    decompress_path_with_options(input, output, &opts)
+}
+
+/// 按 `opts` 将 gzip 文件解压到指定文件；失败时删除不完整的输出文件。
+pub fn decompress_path_with_options(
+    input: &Path,
+    output: &Path,
+    opts: &DecompressOptions,
+) -> io::Result<()> {
     let file = File::open(input)?;
-    decompress_reader_to_path(file, output)
+    decompress_reader_to_path_with_options(file, output, opts)
+}
+
+/// 同 [`decompress_path_with_options`]，并在同一遍读写中计算压缩输入与解压输出的摘要。
+pub fn decompress_path_with_checksum(
+    input: &Path,
+    output: &Path,
+    opts: &DecompressOptions,
+    algorithm: ChecksumAlgorithm,
+) -> io::Result<Digests> {
+    let mut reader = HashingReader::new(File::open(input)?, Some(algorithm));
+    let file = BufWriter::with_capacity(opts.buffer_size.max(1), File::create(output)?);
+    let mut writer = HashingWriter::new(file, Some(algorithm));
+    let result = decompress_reader_to_writer_with_options(&mut reader, &mut writer, opts);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    result?;
+    Ok(Digests {
+        algorithm,
+        compressed: reader.finalize_hex().unwrap_or_default(),
+        uncompressed: writer.finalize_hex().unwrap_or_default(),
+    })
+}
+
+/// 按 `input` 旁的校验文件（见 [`find_sidecar`]）解压并校验；找不到校验文件时返回 `NotFound`，
+/// 校验失败时删除输出并返回 `InvalidData`。
+pub fn decompress_path_verified(
+    input: &Path,
+    output: &Path,
+    opts: &DecompressOptions,
+) -> io::Result<Digests> {
+    let (sidecar, algorithm) = find_sidecar(input).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "找不到 {} 的校验文件（.sha256/.blake3/.crc32）",
+                input.display()
+            ),
+        )
+    })?;
+    let expected = read_sidecar(&sidecar, input, algorithm)?;
+    let actual = decompress_path_with_checksum(input, output, opts, algorithm)?;
+    if let Err(e) = verify_digests(&expected, &actual) {
+        let _ = fs::remove_file(output);
+        return Err(e);
+    }
+    Ok(actual)
 }
 
 /// 将任意 gzip 流解压到指定文件。
 pub fn decompress_reader_to_path<R: Read>(reader: R, output: &Path) -> io::Result<()> {
-    let file = File::create(output)?;
-    decompress_reader_to_writer(reader, file)
+    decompress_reader_to_path_with_options(reader, output, &DecompressOptions::default())
 }
 
-/// 将任意 gzip 流解压到任意写入器。
-pub fn decompress_reader_to_writer<R: Read, W: Write>(reader: R, mut writer: W) -> io::Result<()> {
-    let mut decoder = GzDecoder::new(reader);
-    io::copy(&mut decoder, &mut writer)?;
-    writer.flush()
+/// 将任意 gzip 流解压到指定文件，并施加输出限制；失败时删除不完整的输出文件。
+pub fn decompress_reader_to_path_with_limits<R: Read>(
+    reader: R,
+    output: &Path,
+    limits: DecompressLimits,
+) -> io::Result<()> {
+    let opts = DecompressOptions {
+        limits,
+        ..Default::default()
+    };
+    decompress_reader_to_path_with_options(reader, output, &opts)
+}
+
+/// 按 `opts` 将任意 gzip 流解压到指定文件；失败时删除不完整的输出文件。
+pub fn decompress_reader_to_path_with_options<R: Read>(
+    reader: R,
+    output: &Path,
+    opts: &DecompressOptions,
+) -> io::Result<()> {
+    let file = BufWriter::with_capacity(opts.buffer_size.max(1), File::create(output)?);
+    let result = decompress_reader_to_writer_with_options(reader, file, opts);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+/// 将任意 gzip 流解压到任意写入器；多个成员拼接而成的文件会被完整解压。
+pub fn decompress_reader_to_writer<R: Read, W: Write>(reader: R, writer: W) -> io::Result<()> {
+    decompress_stream(reader, writer, &DecompressOptions::default())
+}
+
+/// 将任意 gzip 流解压到任意写入器；超出 `limits` 时中止并返回 [`LimitExceeded`]。
+///
+/// 已写入写入器的部分数据不会回滚，由调用方决定如何清理。
+pub fn decompress_reader_to_writer_with_limits<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    limits: DecompressLimits,
+) -> io::Result<()> {
+    let opts = DecompressOptions {
+        limits,
+        ..Default::default()
+    };
+    decompress_reader_to_writer_with_options(reader, writer, &opts)
+}
+
+/// 按 `opts` 将任意 gzip 流解压到任意写入器，语义同 [`decompress_reader_to_writer_with_limits`]。
+pub fn decompress_reader_to_writer_with_options<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    opts: &DecompressOptions,
+) -> io::Result<()> {
+    if opts.limits.is_unlimited() {
+        return decompress_stream(reader, writer, opts);
+    }
+    let consumed = Rc::new(Cell::new(0));
+    let reader = CountingReader::new(reader, Rc::clone(&consumed));
+    let writer = LimitedWriter::new(writer, opts.limits, consumed);
+    decompress_stream(reader, writer, opts)
+}
+
+fn decompress_stream<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    opts: &DecompressOptions,
+) -> io::Result<()> {
+    let buffer_size = opts.buffer_size.max(1);
+    if let Some(dict) = &opts.dict {
+        let input = BufReader::with_capacity(buffer_size, reader);
+        return dict::decompress_with_dict(input, writer, dict, buffer_size);
+    }
+    let mut decoder = GzPushDecoder::new();
+    let mut input = vec![0u8; buffer_size];
+    let mut output = vec![0u8; buffer_size];
+    loop {
+        match decoder.read(&mut output)? {
+            Some(0) => return writer.flush(),
+            Some(n) => writer.write_all(&output[..n])?,
+            None => match reader.read(&mut input) {
+                Ok(0) => decoder.finish(),
+                Ok(n) => decoder.push(&input[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            },
+        }
+    }
 }
 
 /// 生成压缩输出文件的默认路径：`<input>.gz`。
@@ -112,7 +700,7 @@ pub fn ensure_writable(output: &Path, force: bool) -> io::Result<()> {
     if output.exists() && !force {
         return Err(io::Error::new(
             io::ErrorKind::AlreadyExists,
-            format!("输出文件已存在: {} (使用 -f 覆盖)", output.display()),
+            format!("目标已存在（使用 -f 覆盖）: {}", output.display()),
         ));
     }
     if let Some(parent) = output.parent() {
@@ -127,3 +715,191 @@ pub fn ensure_writable(output: &Path, force: bool) -> io::Result<()> {
 pub fn same_path(a: &Path, b: &Path) -> bool {
     a == b
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(tag: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rgzip-lib-{}-{}", tag, std::process::id()))
+    }
+
+    /// 按 `sizes` 依次切块送入推送式解码器，输出缓冲区只有 7 字节。
+    fn push_decode(data: &[u8], sizes: &[usize]) -> io::Result<Vec<u8>> {
+        let mut decoder = GzPushDecoder::new();
+        let mut rest = data;
+        let mut sizes = sizes.iter().copied().cycle();
+        let mut out = Vec::new();
+        let mut buf = [0u8; 7];
+        loop {
+            match decoder.read(&mut buf)? {
+                Some(0) => return Ok(out),
+                Some(n) => out.extend_from_slice(&buf[..n]),
+                None if rest.is_empty() => decoder.finish(),
+                None => {
+                    let (chunk, tail) = rest.split_at(sizes.next().unwrap().min(rest.len()));
+                    decoder.push(chunk);
+                    rest = tail;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_decoder_split_anywhere() {
+        let header = HeaderInfo {
+            filename: Some("a.txt".to_string()),
+            ..Default::default()
+        };
+        let mut encoder = gz_encoder(Vec::new(), 6, &header);
+        encoder.write_all(b"hello hello hello\n").unwrap();
+        let mut data = encoder.finish().unwrap();
+        data.extend(compress_reader_to_writer(&b"world"[..], Vec::new(), 1).unwrap());
+
+        for size in [1, 2, 3, 5, 64] {
+            assert_eq!(
+                push_decode(&data, &[size]).unwrap(),
+                b"hello hello hello\nworld"
+            );
+        }
+        for i in 0..=data.len() {
+            let out = push_decode(&data, &[i.max(1), data.len()]).unwrap();
+            assert_eq!(out, b"hello hello hello\nworld", "在 {} 处切分", i);
+        }
+    }
+
+    #[test]
+    fn test_push_decoder_truncation_is_eof() {
+        let data = compress_reader_to_writer(&b"some text"[..], Vec::new(), 6).unwrap();
+        for len in 0..data.len() {
+            let err = push_decode(&data[..len], &[3]).unwrap_err();
+            assert_eq!(
+                err.kind(),
+                io::ErrorKind::UnexpectedEof,
+                "截断为 {} 字节",
+                len
+            );
+        }
+        let mut bad = data.clone();
+        *bad.last_mut().unwrap() ^= 1;
+        let err = push_decode(&bad, &[3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stream_encoder_matches_reader_api() {
+        let opts = CompressOptions {
+            reproducible: Some(0),
+            ..CompressOptions::with_level(9)
+        };
+        let mut encoder = stream_encoder(Vec::new(), &opts).unwrap();
+        encoder.write_all(b"same header").unwrap();
+        let expected =
+            compress_reader_to_writer_with_options(&b"same header"[..], Vec::new(), &opts).unwrap();
+        assert_eq!(encoder.finish().unwrap(), expected);
+
+        let opts = CompressOptions {
+            dict: Some(b"dict".to_vec()),
+            ..Default::default()
+        };
+        let err = stream_encoder(Vec::new(), &opts).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_mmap_and_buffered_paths_match() {
+        let input = temp_path("mmap-in");
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 97) as u8).collect();
+        fs::write(&input, &data).unwrap();
+
+        let mut outputs = Vec::new();
+        for (tag, buffer_size, mmap) in [
+            ("small", 1, false),
+            ("big", 1 << 20, false),
+            ("mmap", 4096, true),
+        ] {
+            let output = temp_path(tag);
+            let opts = CompressOptions {
+                level: 1,
+                buffer_size,
+                mmap,
+                ..Default::default()
+            };
+            compress_path_with_options(&input, &output, &opts).unwrap();
+            outputs.push(fs::read(&output).unwrap());
+            fs::remove_file(&output).unwrap();
+        }
+        // zlib-rs 的快速级别按每次送入的数据量划分块，压缩字节可能随缓冲区大小不同，
+        // 因此只要求各路径都能还原原文。
+        for gz in &outputs {
+            let mut plain = Vec::new();
+            decompress_reader_to_writer(&gz[..], &mut plain).unwrap();
+            assert_eq!(plain, data);
+        }
+        fs::remove_file(&input).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_empty_file_falls_back() {
+        let input = temp_path("mmap-empty");
+        let output = temp_path("mmap-empty-out");
+        fs::write(&input, b"").unwrap();
+        let opts = CompressOptions {
+            mmap: true,
+            ..Default::default()
+        };
+        compress_path_with_options(&input, &output, &opts).unwrap();
+        let mut plain = Vec::new();
+        decompress_reader_to_writer(File::open(&output).unwrap(), &mut plain).unwrap();
+        assert!(plain.is_empty());
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn test_checksum_single_pass_and_verify() {
+        let input = temp_path("sum-in");
+        let output = temp_path("sum-in.gz");
+        let plain_out = temp_path("sum-out");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&input, &data).unwrap();
+
+        for mmap in [false, true] {
+            let opts = CompressOptions {
+                mmap,
+                ..Default::default()
+            };
+            let digests =
+                compress_path_with_checksum(&input, &output, &opts, ChecksumAlgorithm::Sha256)
+                    .unwrap();
+            let expect = |bytes: &[u8]| {
+                let mut h = ChecksumAlgorithm::Sha256.hasher();
+                h.update(bytes);
+                h.finalize_hex()
+            };
+            assert_eq!(digests.uncompressed, expect(&data));
+            assert_eq!(digests.compressed, expect(&fs::read(&output).unwrap()));
+            write_sidecar(&output, &input, &digests).unwrap();
+        }
+
+        let opts = DecompressOptions::default();
+        decompress_path_verified(&output, &plain_out, &opts).unwrap();
+        assert_eq!(fs::read(&plain_out).unwrap(), data);
+
+        // 篡改压缩文件：gzip 本身的 CRC 仍然有效时也应由旁路校验发现。
+        let mut gz = fs::read(&output).unwrap();
+        gz[4] ^= 1;
+        fs::write(&output, &gz).unwrap();
+        let err = decompress_path_verified(&output, &plain_out, &opts).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(!plain_out.exists());
+
+        let sidecar = sidecar_path(&output, ChecksumAlgorithm::Sha256);
+        fs::remove_file(&sidecar).unwrap();
+        let err = decompress_path_verified(&output, &plain_out, &opts).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        for p in [&input, &output] {
+            fs::remove_file(p).unwrap();
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+/// 解压输出的限制，用于处理不可信输入（防 zip bomb）。`None` 表示不限制。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecompressLimits {
+    /// 解压后最多输出的字节数。
+    pub max_output: Option<u64>,
+    /// 解压后字节数与已读取压缩字节数之比的上限。
+    pub max_ratio: Option<u64>,
+}
+
+impl DecompressLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_output.is_none() && self.max_ratio.is_none()
+    }
+}
+
+/// 超出 [`DecompressLimits`] 时返回的错误，包装在 `io::Error`（`ErrorKind::Other`）中，
+/// 可用 [`LimitExceeded::from_io`] 取回。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Output { limit: u64 },
+    Ratio { limit: u64, input: u64, output: u64 },
+}
+
+impl LimitExceeded {
+    /// 从 `io::Error` 中取回限制错误；其他错误返回 `None`。
+    pub fn from_io(err: &io::Error) -> Option<&LimitExceeded> {
+        err.get_ref()
+            .and_then(|e| e.downcast_ref::<LimitExceeded>())
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Output { limit } => {
+                write!(f, "解压输出超过上限 {} 字节", limit)
+            }
+            LimitExceeded::Ratio {
+                limit,
+                input,
+                output,
+            } => write!(
+                f,
+                "解压比超过上限 {}:1（已读取 {} 字节，已输出 {} 字节）",
+                limit, input, output
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl From<LimitExceeded> for io::Error {
+    fn from(e: LimitExceeded) -> Self {
+        io::Error::other(e)
+    }
+}
+
+/// 统计已读取字节数的读取器，计数与 [`LimitedWriter`] 共享。
+pub struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R, count: Rc<Cell<u64>>) -> Self {
+        CountingReader { inner, count }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// 在写入前检查 [`DecompressLimits`] 的写入器，超限时不写入并返回 [`LimitExceeded`]。
+pub struct LimitedWriter<W> {
+    inner: W,
+    limits: DecompressLimits,
+    input: Rc<Cell<u64>>,
+    written: u64,
+}
+
+impl<W> LimitedWriter<W> {
+    /// `input` 为已读取的压缩字节数，通常来自 [`CountingReader`]。
+    pub fn new(inner: W, limits: DecompressLimits, input: Rc<Cell<u64>>) -> Self {
+        LimitedWriter {
+            inner,
+            limits,
+            input,
+            written: 0,
+        }
+    }
+
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    fn check(&self, len: usize) -> Result<(), LimitExceeded> {
+        let output = self.written + len as u64;
+        if let Some(limit) = self.limits.max_output {
+            if output > limit {
+                return Err(LimitExceeded::Output { limit });
+            }
+        }
+        if let Some(limit) = self.limits.max_ratio {
+            let input = self.input.get().max(1);
+            if output > input.saturating_mul(limit) {
+                return Err(LimitExceeded::Ratio {
+                    limit,
+                    input,
+                    output,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check(buf.len())?;
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 解析带可选单位后缀的字节数：`1024`、`64K`、`100M`、`2G`（按 1024 进位，大小写均可）。
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (digits, mult) = match s.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => {
+            let mult: u64 = match c.to_ascii_uppercase() {
+                'K' => 1 << 10,
+                'M' => 1 << 20,
+                'G' => 1 << 30,
+                'T' => 1 << 40,
+                _ => return Err(format!("无法识别的单位: {}", s)),
+            };
+            (&s[..i], mult)
+        }
+        _ => (s, 1),
+    };
+    let n: u64 = digits.parse().map_err(|_| format!("无效的字节数: {}", s))?;
+    n.checked_mul(mult)
+        .ok_or_else(|| format!("字节数溢出: {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn writer(limits: DecompressLimits, input: u64) -> LimitedWriter<Vec<u8>> {
+        LimitedWriter::new(Vec::new(), limits, Rc::new(Cell::new(input)))
+    }
+
+    #[test]
+    fn test_max_output() {
+        let limits = DecompressLimits {
+            max_output: Some(4),
+            ..Default::default()
+        };
+        let mut w = writer(limits, 0);
+        w.write_all(b"abcd").unwrap();
+        let err = w.write_all(b"e").unwrap_err();
+        assert_eq!(
+            LimitExceeded::from_io(&err),
+            Some(&LimitExceeded::Output { limit: 4 })
+        );
+        assert_eq!(w.written(), 4);
+    }
+
+    #[test]
+    fn test_max_ratio() {
+        let limits = DecompressLimits {
+            max_ratio: Some(10),
+            ..Default::default()
+        };
+        let mut w = writer(limits, 2);
+        w.write_all(&[0u8; 20]).unwrap();
+        let err = w.write_all(b"x").unwrap_err();
+        assert!(matches!(
+            LimitExceeded::from_io(&err),
+            Some(LimitExceeded::Ratio { limit: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn test_partial_output_removed() {
+        let data = vec![0u8; 64 * 1024];
+        let gz = crate::compress_reader_to_writer(&data[..], Vec::new(), 9).unwrap();
+        let out = std::env::temp_dir().join(format!("rgzip-limit-{}", std::process::id()));
+        let limits = DecompressLimits {
+            max_ratio: Some(100),
+            ..Default::default()
+        };
+        let err = crate::decompress_reader_to_path_with_limits(&gz[..], &out, limits).unwrap_err();
+        assert!(LimitExceeded::from_io(&err).is_some());
+        assert!(!out.exists());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("64k"), Ok(64 << 10));
+        assert_eq!(parse_size("100M"), Ok(100 << 20));
+        assert!(parse_size("12X").is_err());
+        assert!(parse_size("").is_err());
+    }
+}
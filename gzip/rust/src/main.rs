@@ -1,42 +1,216 @@
+use std::env;
+use std::ffi::OsString;
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
 
-use clap::Parser;
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use rgzip::default_output_for_compress;
 use rgzip::default_output_for_decompress;
 use rgzip::ensure_writable;
 use rgzip::same_path;
-use rgzip::{compress_path, compress_reader_to_path, compress_reader_to_writer, sanitize_level};
-use rgzip::{decompress_path, decompress_reader_to_path, decompress_reader_to_writer};
+use rgzip::{compare_gz_paths, diff_gz_paths, CmpOutcome, Side, MAX_DIFF_SIZE};
+use rgzip::{compress_path_split, compress_reader_split, volume_path};
+use rgzip::{compress_path_with_checksum, decompress_path_verified, write_sidecar};
+use rgzip::{compress_path_with_options, compress_reader_to_path_with_options};
+use rgzip::{compress_reader_to_writer_with_options, sanitize_level, CompressOptions};
+use rgzip::{decompress_path_with_options, decompress_reader_to_path_with_options};
+use rgzip::{decompress_reader_to_writer_with_options, parse_size, DecompressLimits};
+use rgzip::{decompress_volumes_to_path, find_volumes, remove_stale_volumes};
+use rgzip::{default_config_path, expand_level_shorthand, split_env_options, Config};
+use rgzip::{extract_tar_gz, list_tar_gz, TarEntry};
+use rgzip::{find_sidecar, read_gz_header, ChecksumAlgorithm};
+use rgzip::{parse_age, rotate_dir, RotateOptions, RotateOutcome, RotateReport};
+use rgzip::{train_dictionary, DICT_SUFFIX, MAX_DICT_SIZE};
+use rgzip::{DecompressOptions, DEFAULT_BUFFER_SIZE};
 
 #[derive(Parser, Debug)]
-#[command(name = "rgzip", version, about = "A simple gzip CLI written in Rust")]
+#[command(
+    name = "rgzip",
+    version,
+    about = "A simple gzip CLI written in Rust",
+    args_conflicts_with_subcommands = true,
+    args_override_self = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// 解压模式（默认压缩）
     #[arg(short = 'd', long = "decompress")]
     decompress: bool,
 
-    /// 指定输出文件路径（默认：压缩为 <INPUT>.gz；解压为去掉 .gz 或追加 .out）
+    /// 指定输出文件路径（默认：压缩为 <INPUT>.gz；解压为去掉 .gz）
     #[arg(short = 'o', long = "output")]
     output: Option<PathBuf>,
 
     /// 覆盖已有输出文件
-    #[arg(short = 'f', long = "force")]
+    #[arg(short = 'f', long = "force", overrides_with = "no_force")]
     force: bool,
 
+    /// 不覆盖已有输出文件（取消配置文件或环境变量中的 --force）
+    #[arg(long = "no-force", overrides_with = "force")]
+    no_force: bool,
+
     /// 压缩级别（0-9，默认 6）
     #[arg(short = 'l', long = "level", default_value_t = 6)]
     level: u32,
 
-    /// 保留源文件（默认成功后删除源文件）
-    #[arg(short = 'k', long = "keep")]
+    /// 保留源文件（默认；配合 --rotate 时保留轮转的原文件）
+    #[arg(short = 'k', long = "keep", overrides_with = "no_keep")]
     keep: bool,
 
+    /// 成功后删除源文件（同 GNU gzip 的默认行为）
+    #[arg(long = "no-keep", overrides_with = "keep")]
+    no_keep: bool,
+
+    /// 不在 gzip 头中记录原文件名与修改时间；解压时不按 gzip 头恢复修改时间
+    #[arg(short = 'n', long = "no-name", overrides_with = "name")]
+    no_name: bool,
+
+    /// 记录并恢复原文件名与修改时间（默认；取消配置文件或环境变量中的 --no-name）
+    #[arg(short = 'N', long = "name", overrides_with = "no_name")]
+    name: bool,
+
+    /// 读写缓冲区大小，支持 K/M/G 后缀（默认 128K）
+    #[arg(long = "buffer-size", value_name = "BYTES", value_parser = parse_size)]
+    buffer_size: Option<u64>,
+
+    /// 压缩普通文件时使用内存映射读取输入
+    #[arg(long = "mmap", overrides_with = "no_mmap")]
+    mmap: bool,
+
+    /// 不使用内存映射（取消配置文件或环境变量中的 --mmap）
+    #[arg(long = "no-mmap", overrides_with = "mmap")]
+    no_mmap: bool,
+
+    /// 解压输出的字节上限，支持 K/M/G 后缀（处理不可信输入）
+    #[arg(long = "max-output", value_name = "BYTES", value_parser = parse_size)]
+    max_output: Option<u64>,
+
+    /// 解压比（输出/输入）上限，超过即中止
+    #[arg(long = "max-ratio", value_name = "N")]
+    max_ratio: Option<u64>,
+
+    /// 可复现输出：mtime 取 0（或 SOURCE_DATE_EPOCH 与文件 mtime 的较小值），OS 字节固定为 255；
+    /// 设置 SOURCE_DATE_EPOCH 时自动启用
+    #[arg(long = "reproducible", overrides_with = "no_reproducible")]
+    reproducible: bool,
+
+    /// 取消配置文件或环境变量中的 --reproducible（设置 SOURCE_DATE_EPOCH 时仍自动启用）
+    #[arg(long = "no-reproducible", overrides_with = "reproducible")]
+    no_reproducible: bool,
+
+    /// 压缩时写出校验文件 <OUTPUT>.<ALG>，记录压缩文件与原始内容的摘要（sha256、crc32、blake3）
+    #[arg(long = "checksum", value_name = "ALG")]
+    checksum: Option<ChecksumAlgorithm>,
+
+    /// 解压时按 <INPUT>.sha256/.blake3/.crc32 校验压缩文件与解压内容，失败时删除输出
+    #[arg(long = "verify-checksum")]
+    verify_checksum: bool,
+
+    /// 将压缩输出拆分为不超过该大小的分卷 <OUTPUT>.000、.001……（每卷是完整的 gzip 成员）
+    #[arg(
+        long = "split-size",
+        value_name = "BYTES",
+        value_parser = parse_size,
+        conflicts_with_all = ["dict", "checksum"]
+    )]
+    split_size: Option<u64>,
+
+    /// 日志轮转：递归压缩目录中修改时间早于 --older-than 的文件，校验后删除原文件并输出报告
+    #[arg(
+        long = "rotate",
+        value_name = "DIR",
+        requires = "older_than",
+        conflicts_with_all = ["decompress", "output", "dict", "split_size", "checksum"]
+    )]
+    rotate: Option<PathBuf>,
+
+    /// 配合 --rotate：只处理修改时间早于该时长的文件（如 30m、12h、7d、2w）
+    #[arg(long = "older-than", value_name = "AGE", value_parser = parse_age, requires = "rotate")]
+    older_than: Option<Duration>,
+
+    /// 配合 --rotate：只列出将被压缩的文件，不做任何修改
+    #[arg(long = "dry-run", requires = "rotate")]
+    dry_run: bool,
+
+    /// 使用预设字典压缩/解压（输出为 zlib 流，默认后缀 .zz；字典可由 train-dict 生成）
+    #[arg(long = "dict", value_name = "FILE")]
+    dict: Option<PathBuf>,
+
     /// 输入文件（留空则使用标准输入）
     input: Option<PathBuf>,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 按解压后的内容比较两个 gzip 文件（忽略头部差异）
+    Cmp(CmpArgs),
+    /// 流式处理 .tar.gz：列出或解包成员（不依赖外部 tar）
+    Tar {
+        #[command(subcommand)]
+        action: TarAction,
+    },
+    /// 从样本文件的公共子串训练预设字典（供 --dict 使用）
+    TrainDict(TrainDictArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum TarAction {
+    /// 列出成员的权限、大小与名称
+    List {
+        /// 输入 .tar.gz 文件（留空则使用标准输入）
+        input: Option<PathBuf>,
+    },
+    /// 解包到目录，拒绝绝对路径、`..` 与经由符号链接的写入
+    Extract {
+        /// 解包目标目录
+        #[arg(short = 'C', long = "directory", default_value = ".")]
+        directory: PathBuf,
+
+        /// 打印解包的成员
+        #[arg(short = 'v', long = "verbose")]
+        verbose: bool,
+
+        /// 输入 .tar.gz 文件（留空则使用标准输入）
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct TrainDictArgs {
+    /// 字典输出路径（留空则写到标准输出）
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+
+    /// 字典大小上限，支持 K 后缀（最大 32K，即 deflate 窗口大小）
+    #[arg(long = "max-size", value_name = "BYTES", value_parser = parse_size, default_value = "32K")]
+    max_size: u64,
+
+    /// 样本文件（每个文件视为一条记录）
+    #[arg(required = true)]
+    samples: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct CmpArgs {
+    /// 文本内容不同时输出统一格式 diff
+    #[arg(long = "diff")]
+    diff: bool,
+
+    /// diff 上下文行数
+    #[arg(short = 'U', long = "unified", default_value_t = 3, requires = "diff")]
+    context: usize,
+
+    /// 第一个 gzip 文件
+    a: PathBuf,
+
+    /// 第二个 gzip 文件
+    b: PathBuf,
+}
+
 fn main() {
     if let Err(e) = real_main() {
         eprintln!("Error: {}", e);
@@ -45,89 +219,715 @@ fn main() {
 }
 
 fn real_main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+    let argv: Vec<OsString> = env::args_os().collect();
+    let argv = if starts_with_subcommand(&argv) {
+        argv
+    } else {
+        let config = match default_config_path() {
+            Some(path) => Config::load(&path)?,
+            None => Config::default(),
+        };
+        let gzip_env = env::var("GZIP").ok();
+        let rgzip_env = env::var("RGZIP").ok();
+        with_default_options(argv, &config, gzip_env.as_deref(), rgzip_env.as_deref())?
+    };
+    let mut cli = Cli::parse_from(argv);
+
+    if let Some(command) = cli.command.take() {
+        return match command {
+            Command::Cmp(args) => run_cmp(args),
+            Command::Tar { action } => run_tar(action),
+            Command::TrainDict(args) => run_train_dict(args),
+        };
+    }
 
-    if cli.decompress {
+    if cli.rotate.is_some() {
+        run_rotate(cli)
+    } else if cli.decompress {
         run_decompress(cli)
     } else {
         run_compress(cli)
     }
 }
 
+fn run_rotate(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let (Some(dir), Some(older_than)) = (&cli.rotate, cli.older_than) else {
+        return Err("--rotate 需要 --older-than".into());
+    };
+    // 不用 clap 的 conflicts_with：冲突时 clap 会放宽 --older-than 等对 --rotate 的 requires 检查。
+    if let Some(input) = &cli.input {
+        return Err(format!("--rotate 不能与输入文件同时使用: {}", input.display()).into());
+    }
+    if !dir.is_dir() {
+        return Err(format!("目录不存在: {}", dir.display()).into());
+    }
+    let opts = CompressOptions {
+        level: sanitize_level(cli.level),
+        buffer_size: buffer_size(&cli),
+        mmap: cli.mmap,
+        no_name: cli.no_name,
+        dict: None,
+        reproducible: reproducible_epoch(
+            cli.reproducible,
+            env::var("SOURCE_DATE_EPOCH").ok().as_deref(),
+        )?,
+    };
+    let rotate = RotateOptions {
+        older_than,
+        dry_run: cli.dry_run,
+        keep: cli.keep,
+    };
+    let report = rotate_dir(dir, &rotate, &opts)?;
+    print_rotate_report(&report, cli.dry_run);
+    match report.failed() {
+        0 => Ok(()),
+        n => Err(format!("{} 个文件处理失败，原文件已保留", n).into()),
+    }
+}
+
+fn print_rotate_report(report: &RotateReport, dry_run: bool) {
+    for entry in &report.entries {
+        let path = entry.path.display();
+        match &entry.outcome {
+            RotateOutcome::Compressed { output, compressed } => println!(
+                "已压缩 {} -> {}（{} -> {} 字节）",
+                path,
+                output.display(),
+                entry.size,
+                compressed
+            ),
+            RotateOutcome::WouldCompress { output } => {
+                println!(
+                    "将压缩 {} -> {}（{} 字节）",
+                    path,
+                    output.display(),
+                    entry.size
+                )
+            }
+            RotateOutcome::Skipped(reason) => println!("跳过 {}：{}", path, reason),
+            RotateOutcome::Failed(err) => println!("失败 {}：{}", path, err),
+        }
+    }
+    let (before, after) = report.bytes();
+    println!(
+        "{}{} 个文件，跳过 {} 个，失败 {} 个{}",
+        if dry_run {
+            "演练：将压缩 "
+        } else {
+            "压缩 "
+        },
+        report.compressed(),
+        report.skipped(),
+        report.failed(),
+        if dry_run {
+            String::new()
+        } else {
+            format!("；{} -> {} 字节", before, after)
+        }
+    );
+}
+
+/// 子命令（cmp、tar 等）不受默认选项影响。
+fn starts_with_subcommand(argv: &[OsString]) -> bool {
+    let Some(first) = argv.get(1).and_then(|a| a.to_str()) else {
+        return false;
+    };
+    let cmd = Cli::command();
+    first == "help" || cmd.get_subcommands().any(|sub| sub.get_name() == first)
+}
+
+/// 在命令行参数前插入默认选项，优先级从低到高：
+/// 配置文件 `[defaults]` < 配置文件后缀规则 `[[rules]]` < `GZIP` < `RGZIP` < 命令行。
+///
+/// 依赖 clap 的 `args_override_self`：同一选项出现多次时以最后一次为准。
+fn with_default_options(
+    argv: Vec<OsString>,
+    config: &Config,
+    gzip_env: Option<&str>,
+    rgzip_env: Option<&str>,
+) -> Result<Vec<OsString>, String> {
+    let mut iter = argv.into_iter();
+    let argv0 = iter.next().unwrap_or_else(|| OsString::from("rgzip"));
+
+    let mut cli_args = Vec::new();
+    let mut positional_only = false;
+    for arg in iter {
+        match arg.to_str() {
+            Some("--") => {
+                positional_only = true;
+                cli_args.push(arg);
+            }
+            Some(s) if !positional_only => cli_args.push(expand_level_shorthand(s).into()),
+            _ => cli_args.push(arg),
+        }
+    }
+
+    let mut env_args = Vec::new();
+    for (name, value) in [("GZIP", gzip_env), ("RGZIP", rgzip_env)] {
+        if let Some(value) = value {
+            env_args.extend(split_env_options(name, value)?);
+        }
+    }
+
+    // 先解析一次以确定输入文件，用于匹配后缀规则。
+    let assemble = |config_args: &[String]| {
+        std::iter::once(argv0.clone())
+            .chain(config_args.iter().map(OsString::from))
+            .chain(env_args.iter().map(OsString::from))
+            .chain(cli_args.iter().cloned())
+            .collect::<Vec<OsString>>()
+    };
+    let input = Cli::try_parse_from(assemble(&[]))
+        .ok()
+        .and_then(|cli| cli.input);
+    Ok(assemble(&config.args_for(input.as_deref())))
+}
+
 fn run_compress(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let level = sanitize_level(cli.level);
-    match cli.input {
+    let opts = CompressOptions {
+        level: sanitize_level(cli.level),
+        buffer_size: buffer_size(&cli),
+        mmap: cli.mmap,
+        no_name: cli.no_name,
+        dict: read_dict(&cli)?,
+        reproducible: reproducible_epoch(
+            cli.reproducible,
+            env::var("SOURCE_DATE_EPOCH").ok().as_deref(),
+        )?,
+    };
+    match cli.input.clone() {
         Some(input) => {
             if !input.exists() {
                 return Err(format!("输入文件不存在: {}", input.display()).into());
             }
-            let output = match cli.output {
+            if cli.output.is_none() && has_gz_suffix(&input) {
+                eprintln!("跳过已为 .gz 的文件: {}", input.display());
+                return Ok(());
+            }
+            let output = match cli.output.clone() {
                 Some(p) => p,
+                None if cli.dict.is_some() => append_suffix(&input, DICT_SUFFIX),
                 None => default_output_for_compress(&input),
             };
+            if let Some(split_size) = cli.split_size {
+                compress_to_volumes(&cli, &output, split_size, |base| {
+                    compress_path_split(&input, base, &opts, split_size)
+                })?;
+                if cli.no_keep {
+                    fs::remove_file(&input)?;
+                }
+                return Ok(());
+            }
             ensure_writable(&output, cli.force)?;
             if same_path(&input, &output) {
                 return Err("输出路径与输入文件相同".into());
             }
-            compress_path(&input, &output, level)?;
-            if !cli.keep {
+            let digests = match cli.checksum {
+                Some(algorithm) => {
+                    compress_path_with_checksum(&input, &output, &opts, algorithm).map(Some)
+                }
+                None => compress_path_with_options(&input, &output, &opts).map(|()| None),
+            }
+            .map_err(|e| failed("压缩", &input, &output, e))?;
+            if let Some(digests) = digests {
+                write_sidecar(&output, &input, &digests)?;
+            }
+            if cli.no_keep {
                 fs::remove_file(&input)?;
             }
         }
         None => {
+            if cli.checksum.is_some() {
+                return Err("--checksum 需要指定输入文件".into());
+            }
+            if let Some(split_size) = cli.split_size {
+                let base = cli.output.clone().ok_or("--split-size 需要输入文件或 -o")?;
+                return compress_to_volumes(&cli, &base, split_size, |base| {
+                    compress_reader_split(io::stdin().lock(), base, &opts, split_size)
+                });
+            }
             // stdin -> (output|stdout)
             if let Some(out_path) = cli.output {
                 ensure_writable(&out_path, cli.force)?;
                 let stdin = io::stdin();
                 let reader = stdin.lock();
-                compress_reader_to_path(reader, &out_path, level)?;
+                compress_reader_to_path_with_options(reader, &out_path, &opts)?;
             } else {
                 // stdout
                 let stdin = io::stdin();
                 let reader = stdin.lock();
                 let stdout = io::stdout();
                 let handle = stdout.lock();
-                let _ = compress_reader_to_writer(reader, handle, level)?;
+                let _ = compress_reader_to_writer_with_options(reader, handle, &opts)?;
             }
         }
     }
     Ok(())
 }
 
+/// 生成分卷 `<base>.000`……；`-f` 时删除上次留下的多余旧分卷，避免解压时被误接在末尾。
+fn compress_to_volumes(
+    cli: &Cli,
+    base: &Path,
+    split_size: u64,
+    compress: impl FnOnce(&Path) -> io::Result<Vec<PathBuf>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first = volume_path(base, 0);
+    ensure_writable(&first, cli.force)?;
+    if cli
+        .input
+        .as_deref()
+        .is_some_and(|input| same_path(input, &first))
+    {
+        return Err("输出路径与输入文件相同".into());
+    }
+    let volumes = compress(base)?;
+    remove_stale_volumes(base, volumes.len())?;
+    eprintln!(
+        "已生成 {} 个分卷（每卷不超过 {} 字节）: {} …",
+        volumes.len(),
+        split_size,
+        volumes[0].display()
+    );
+    Ok(())
+}
+
+fn buffer_size(cli: &Cli) -> usize {
+    cli.buffer_size
+        .map(|n| n.clamp(1, usize::MAX as u64) as usize)
+        .unwrap_or(DEFAULT_BUFFER_SIZE)
+}
+
+/// 可复现模式的 mtime 上限：设置了 `SOURCE_DATE_EPOCH` 时取其值（并自动启用），
+/// 否则 `--reproducible` 取 0；超出 gzip 头 32 位范围的值按上限截断。
+fn reproducible_epoch(flag: bool, source_date_epoch: Option<&str>) -> Result<Option<u32>, String> {
+    match source_date_epoch.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(v) => v
+            .parse::<u64>()
+            .map(|n| Some(n.min(u32::MAX as u64) as u32))
+            .map_err(|_| format!("SOURCE_DATE_EPOCH 不是有效的秒数: {}", v)),
+        None => Ok(flag.then_some(0)),
+    }
+}
+
+fn read_dict(cli: &Cli) -> io::Result<Option<Vec<u8>>> {
+    cli.dict
+        .as_ref()
+        .map(|path| {
+            fs::read(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+        })
+        .transpose()
+}
+
+fn append_suffix(input: &Path, suffix: &str) -> PathBuf {
+    let mut name = input.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 去掉 `.zz` 后缀；没有该后缀时与 gzip 一样追加 `.out`。
+fn strip_dict_suffix(input: &Path) -> PathBuf {
+    match input.to_str().and_then(|s| s.strip_suffix(DICT_SUFFIX)) {
+        Some(stem) if !stem.is_empty() => PathBuf::from(stem),
+        _ => append_suffix(input, ".out"),
+    }
+}
+
+/// 与 gzip-go 一致，只按大小写敏感的 `.gz` 后缀判断。
+fn has_gz_suffix(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(".gz"))
+}
+
+/// 文件到文件的压缩/解压失败时附上源与目标路径，格式与 gzip-go 相同。
+fn failed(action: &str, input: &Path, output: &Path, e: io::Error) -> String {
+    format!(
+        "{}失败 {} -> {}: {}",
+        action,
+        input.display(),
+        output.display(),
+        e
+    )
+}
+
+/// 按 gzip 头中记录的 mtime 设置解压输出的修改时间（为 0 表示未记录）；尽力而为，失败时忽略。
+fn restore_mtime(input: &Path, output: &Path) {
+    let Ok(header) = fs::File::open(input).and_then(read_gz_header) else {
+        return;
+    };
+    if header.mtime != 0 {
+        let mtime = UNIX_EPOCH + Duration::from_secs(header.mtime.into());
+        let _ = fs::File::options()
+            .write(true)
+            .open(output)
+            .and_then(|f| f.set_modified(mtime));
+    }
+}
+
 fn run_decompress(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    match cli.input {
+    let opts = DecompressOptions {
+        buffer_size: buffer_size(&cli),
+        limits: DecompressLimits {
+            max_output: cli.max_output,
+            max_ratio: cli.max_ratio,
+        },
+        dict: read_dict(&cli)?,
+    };
+    match cli.input.clone() {
         Some(input) => {
+            if let Some((base, volumes)) = find_volumes(&input) {
+                return decompress_volume_set(&cli, &base, &volumes, &opts);
+            }
             if !input.exists() {
                 return Err(format!("输入文件不存在: {}", input.display()).into());
             }
-            let output = match cli.output {
+            if cli.output.is_none() && cli.dict.is_none() && !has_gz_suffix(&input) {
+                eprintln!("跳过非 .gz 文件: {}", input.display());
+                return Ok(());
+            }
+            let output = match cli.output.clone() {
                 Some(p) => p,
+                None if cli.dict.is_some() => strip_dict_suffix(&input),
                 None => default_output_for_decompress(&input),
             };
             ensure_writable(&output, cli.force)?;
             if same_path(&input, &output) {
                 return Err("输出路径与输入文件相同".into());
             }
-            decompress_path(&input, &output)?;
-            if !cli.keep {
+            if cli.verify_checksum {
+                decompress_path_verified(&input, &output, &opts).map(drop)
+            } else {
+                decompress_path_with_options(&input, &output, &opts)
+            }
+            .map_err(|e| failed("解压", &input, &output, e))?;
+            if cli.dict.is_none() && !cli.no_name {
+                restore_mtime(&input, &output);
+            }
+            if cli.no_keep {
                 fs::remove_file(&input)?;
+                // 校验文件描述的是已删除的压缩文件，一并删除。
+                if cli.verify_checksum {
+                    if let Some((sidecar, _)) = find_sidecar(&input) {
+                        fs::remove_file(sidecar)?;
+                    }
+                }
             }
         }
         None => {
+            if cli.verify_checksum {
+                return Err("--verify-checksum 需要指定输入文件".into());
+            }
             // stdin -> (output|stdout)
             if let Some(out_path) = cli.output {
                 ensure_writable(&out_path, cli.force)?;
                 let stdin = io::stdin();
                 let reader = stdin.lock();
-                decompress_reader_to_path(reader, &out_path)?;
+                decompress_reader_to_path_with_options(reader, &out_path, &opts)?;
             } else {
                 // stdout
                 let stdin = io::stdin();
                 let stdout = io::stdout();
                 let out = stdout.lock();
-                decompress_reader_to_writer(stdin.lock(), out)?;
+                decompress_reader_to_writer_with_options(stdin.lock(), out, &opts)?;
             }
         }
     }
     Ok(())
 }
+
+/// 解压 `<base>.000`、`.001`……组成的分卷集合，默认输出为去掉 `.gz` 的基础路径。
+fn decompress_volume_set(
+    cli: &Cli,
+    base: &Path,
+    volumes: &[PathBuf],
+    opts: &DecompressOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if cli.verify_checksum || cli.dict.is_some() {
+        return Err("分卷解压不支持 --verify-checksum 与 --dict".into());
+    }
+    let output = match &cli.output {
+        Some(p) => p.clone(),
+        None => default_output_for_decompress(base),
+    };
+    ensure_writable(&output, cli.force)?;
+    if volumes.iter().any(|v| same_path(v, &output)) {
+        return Err("输出路径与输入文件相同".into());
+    }
+    decompress_volumes_to_path(volumes, &output, opts)
+        .map_err(|e| failed("解压", base, &output, e))?;
+    if !cli.no_name {
+        restore_mtime(&volumes[0], &output);
+    }
+    if cli.no_keep {
+        for volume in volumes {
+            fs::remove_file(volume)?;
+        }
+    }
+    Ok(())
+}
+
+/// 退出码与 cmp 一致：0 相同，1 不同，2 出错。
+fn run_cmp(args: CmpArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let same = match cmp_files(&args) {
+        Ok(same) => same,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+    if !same {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmp_files(args: &CmpArgs) -> io::Result<bool> {
+    if args.diff {
+        if let Some(diff) = diff_gz_paths(&args.a, &args.b, args.context)? {
+            print!("{}", diff);
+            return Ok(diff.is_empty());
+        }
+        eprintln!(
+            "提示: 内容不是文本或解压后超过 {} MiB，改为按字节比较",
+            MAX_DIFF_SIZE >> 20
+        );
+    }
+
+    let (a, b) = (args.a.display(), args.b.display());
+    match compare_gz_paths(&args.a, &args.b)? {
+        CmpOutcome::Identical { .. } => Ok(true),
+        CmpOutcome::Differ { offset, line } => {
+            println!("{} {} 不同: 解压后偏移 {}，第 {} 行", a, b, offset, line);
+            Ok(false)
+        }
+        CmpOutcome::Eof { side, len } => {
+            let shorter = if side == Side::Left { &a } else { &b };
+            println!(
+                "{} 在 {} 字节后结束（{} 与 {} 前缀相同）",
+                shorter, len, a, b
+            );
+            Ok(false)
+        }
+    }
+}
+
+fn run_tar(action: TarAction) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        TarAction::List { input } => {
+            let entries = match input {
+                Some(path) => list_tar_gz(fs::File::open(path)?)?,
+                None => list_tar_gz(io::stdin().lock())?,
+            };
+            for entry in &entries {
+                print_tar_entry(entry);
+            }
+        }
+        TarAction::Extract {
+            directory,
+            verbose,
+            input,
+        } => {
+            let entries = match input {
+                Some(path) => extract_tar_gz(fs::File::open(path)?, &directory)?,
+                None => extract_tar_gz(io::stdin().lock(), &directory)?,
+            };
+            if verbose {
+                for entry in &entries {
+                    println!("{}", entry.path);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_train_dict(args: TrainDictArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let samples = args
+        .samples
+        .iter()
+        .map(|path| fs::read(path).map_err(|e| format!("{}: {}", path.display(), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let max_size = args.max_size.min(MAX_DICT_SIZE as u64) as usize;
+    let dict = train_dictionary(&samples, max_size);
+    if dict.is_empty() {
+        return Err("样本之间没有足够的公共内容，无法生成字典".into());
+    }
+    match args.output {
+        Some(path) => fs::write(path, &dict)?,
+        None => io::stdout().lock().write_all(&dict)?,
+    }
+    eprintln!("字典大小 {} 字节（样本 {} 个）", dict.len(), samples.len());
+    Ok(())
+}
+
+fn print_tar_entry(entry: &TarEntry) {
+    match &entry.link_name {
+        Some(link) => println!(
+            "{} {:>10} {} -> {}",
+            entry.mode_string(),
+            entry.size,
+            entry.path,
+            link
+        ),
+        None => println!("{} {:>10} {}", entry.mode_string(), entry.size, entry.path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[defaults]
+level = 9
+no_name = true
+
+[[rules]]
+suffix = ".log"
+level = 1
+"#;
+
+    fn parse(args: &[&str], config: &str, gzip: Option<&str>, rgzip: Option<&str>) -> Cli {
+        let argv = std::iter::once("rgzip")
+            .chain(args.iter().copied())
+            .map(OsString::from)
+            .collect();
+        let config = Config::from_toml_str(config).unwrap();
+        Cli::try_parse_from(with_default_options(argv, &config, gzip, rgzip).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_defaults() {
+        let cli = parse(&["a.txt"], "", None, None);
+        assert_eq!(cli.level, 6);
+        assert!(!cli.no_name);
+    }
+
+    #[test]
+    fn test_precedence_order() {
+        // 配置文件 [defaults]
+        let cli = parse(&["a.txt"], CONFIG, None, None);
+        assert_eq!(cli.level, 9);
+        assert!(cli.no_name);
+        // 后缀规则覆盖 [defaults]
+        assert_eq!(parse(&["app.log"], CONFIG, None, None).level, 1);
+        // GZIP 覆盖配置文件
+        assert_eq!(parse(&["app.log"], CONFIG, Some("-5"), None).level, 5);
+        // RGZIP 覆盖 GZIP
+        assert_eq!(parse(&["app.log"], CONFIG, Some("-5"), Some("-4")).level, 4);
+        // 命令行覆盖一切
+        assert_eq!(
+            parse(&["-3", "app.log"], CONFIG, Some("-5"), Some("-4")).level,
+            3
+        );
+        assert_eq!(
+            parse(&["app.log", "--level", "2"], CONFIG, Some("-5"), None).level,
+            2
+        );
+    }
+
+    #[test]
+    fn test_negating_flags_override_config() {
+        const ALL_ON: &str = r#"
+[defaults]
+keep = true
+force = true
+no_name = true
+mmap = true
+reproducible = true
+"#;
+        let cli = parse(&["a.txt"], ALL_ON, None, None);
+        assert!(cli.keep && cli.force && cli.no_name && cli.mmap && cli.reproducible);
+
+        let args = [
+            "--no-keep",
+            "--no-force",
+            "-N",
+            "--no-mmap",
+            "--no-reproducible",
+            "a.txt",
+        ];
+        let cli = parse(&args, ALL_ON, None, None);
+        assert!(!cli.keep && !cli.force && !cli.no_name && !cli.mmap && !cli.reproducible);
+        // 环境变量同样可以取消配置文件中的选项；同一选项以最后出现的为准。
+        assert!(!parse(&["a.txt"], ALL_ON, Some("--no-keep"), None).keep);
+        assert!(parse(&["--no-keep", "-k", "a.txt"], ALL_ON, None, None).keep);
+        assert!(!parse(&["-k", "--no-keep", "a.txt"], "", None, None).keep);
+    }
+
+    #[test]
+    fn test_source_kept_unless_no_keep() {
+        // 与 gzip-go 一致：默认保留源文件，只有 --no-keep（或配置 keep = false）时删除。
+        assert!(!parse(&["a.txt"], "", None, None).no_keep);
+        assert!(parse(&["--no-keep", "a.txt"], "", None, None).no_keep);
+        assert!(parse(&["a.txt"], "[defaults]\nkeep = false\n", None, None).no_keep);
+        assert!(!parse(&["a.txt"], "[defaults]\nkeep = false\n", None, Some("-k")).no_keep);
+    }
+
+    #[test]
+    fn test_env_options_only() {
+        let config = Config::default();
+        let argv = vec![OsString::from("rgzip")];
+        assert!(with_default_options(argv, &config, Some("-9 secret.txt"), None).is_err());
+        let cli = parse(&[], "", Some("-9 -n"), None);
+        assert_eq!(cli.level, 9);
+        assert!(cli.no_name);
+        assert!(cli.input.is_none());
+    }
+
+    #[test]
+    fn test_reproducible_epoch() {
+        assert_eq!(reproducible_epoch(false, None), Ok(None));
+        assert_eq!(reproducible_epoch(true, None), Ok(Some(0)));
+        assert_eq!(
+            reproducible_epoch(false, Some("1700000000")),
+            Ok(Some(1_700_000_000))
+        );
+        assert_eq!(reproducible_epoch(false, Some("")), Ok(None));
+        assert_eq!(
+            reproducible_epoch(true, Some("99999999999")),
+            Ok(Some(u32::MAX))
+        );
+        assert!(reproducible_epoch(false, Some("yesterday")).is_err());
+    }
+
+    #[test]
+    fn test_dict_output_names() {
+        assert_eq!(
+            append_suffix(Path::new("a/rec.json"), DICT_SUFFIX),
+            Path::new("a/rec.json.zz")
+        );
+        assert_eq!(
+            strip_dict_suffix(Path::new("a/rec.json.zz")),
+            Path::new("a/rec.json")
+        );
+        assert_eq!(
+            strip_dict_suffix(Path::new("rec.gz")),
+            Path::new("rec.gz.out")
+        );
+    }
+
+    #[test]
+    fn test_rotate_flags() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("rgzip").chain(args.iter().copied()))
+        };
+        let cli = parse(&["--rotate", "logs", "--older-than", "7d", "--dry-run"]).unwrap();
+        assert_eq!(cli.older_than, Some(Duration::from_secs(7 * 86_400)));
+        assert!(parse(&["--rotate", "logs"]).is_err());
+        assert!(parse(&["--older-than", "7d", "file.log"]).is_err());
+        assert!(parse(&["--dry-run", "file.log"]).is_err());
+        assert!(parse(&["--rotate", "logs", "--older-than", "7d", "-d"]).is_err());
+    }
+
+    #[test]
+    fn test_subcommand_detection() {
+        let argv = |a: &str| vec![OsString::from("rgzip"), OsString::from(a)];
+        assert!(starts_with_subcommand(&argv("cmp")));
+        assert!(starts_with_subcommand(&argv("tar")));
+        assert!(starts_with_subcommand(&argv("train-dict")));
+        assert!(!starts_with_subcommand(&argv("file.txt")));
+    }
+}
@@ -0,0 +1,390 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::checksum::{HashingReader, HashingWriter};
+use crate::{compress_path_with_checksum, default_output_for_compress};
+use crate::{decompress_reader_to_writer_with_options, ChecksumAlgorithm, Digests};
+use crate::{verify_digests, CompressOptions, DecompressOptions};
+
+/// 轮转时视为已压缩、不再处理的后缀。
+const COMPRESSED_SUFFIXES: [&str; 4] = ["gz", "tgz", "zz", "z"];
+
+/// 解析时长：`30s`、`15m`、`12h`、`7d`、`2w`（必须带单位）。
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (digits, unit) = s.split_at(s.len() - s.chars().last().map_or(0, char::len_utf8));
+    let secs: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return Err(format!("无效的时长: {}（需带单位 s/m/h/d/w，如 7d）", s)),
+    };
+    let n: u64 = digits.parse().map_err(|_| format!("无效的时长: {}", s))?;
+    n.checked_mul(secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("时长溢出: {}", s))
+}
+
+/// [`rotate_dir`] 的选项。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotateOptions {
+    /// 只处理修改时间早于此时长之前的文件。
+    pub older_than: Duration,
+    /// 只列出将被压缩的文件，不写入任何内容。
+    pub dry_run: bool,
+    /// 校验通过后保留原文件。
+    pub keep: bool,
+}
+
+/// 单个文件的轮转结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotateOutcome {
+    /// 已压缩并校验（未设置 `keep` 时已删除原文件）。
+    Compressed { output: PathBuf, compressed: u64 },
+    /// 演练模式下将被压缩。
+    WouldCompress { output: PathBuf },
+    /// 未处理的原因（如输出已存在）。
+    Skipped(String),
+    /// 处理失败；原文件保留。
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotateEntry {
+    pub path: PathBuf,
+    /// 原文件大小。
+    pub size: u64,
+    pub outcome: RotateOutcome,
+}
+
+/// [`rotate_dir`] 的报告，条目按路径排序。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RotateReport {
+    pub entries: Vec<RotateEntry>,
+}
+
+impl RotateReport {
+    fn count(&self, pred: impl Fn(&RotateOutcome) -> bool) -> usize {
+        self.entries.iter().filter(|e| pred(&e.outcome)).count()
+    }
+
+    /// 已压缩（或演练模式下将被压缩）的文件数。
+    pub fn compressed(&self) -> usize {
+        self.count(|o| {
+            matches!(
+                o,
+                RotateOutcome::Compressed { .. } | RotateOutcome::WouldCompress { .. }
+            )
+        })
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, RotateOutcome::Skipped(_)))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, RotateOutcome::Failed(_)))
+    }
+
+    /// 压缩前后的总字节数（仅统计已压缩的文件）。
+    pub fn bytes(&self) -> (u64, u64) {
+        self.entries
+            .iter()
+            .filter_map(|e| match e.outcome {
+                RotateOutcome::Compressed { compressed, .. } => Some((e.size, compressed)),
+                _ => None,
+            })
+            .fold((0, 0), |(a, b), (x, y)| (a + x, b + y))
+    }
+}
+
+/// 压缩 `dir` 下（递归）修改时间早于 `older_than` 的普通文件，输出为同目录下的 `<文件>.gz`。
+///
+/// 压缩结果保留原文件的 mtime 与权限；删除原文件前先完整解压一遍，核对压缩数据与解压内容的
+/// SHA-256，并确认原文件在压缩期间未被修改。符号链接与已压缩的文件（`.gz` 等）不处理。
+pub fn rotate_dir(
+    dir: &Path,
+    rotate: &RotateOptions,
+    opts: &CompressOptions,
+) -> io::Result<RotateReport> {
+    let cutoff = SystemTime::now()
+        .checked_sub(rotate.older_than)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut files = Vec::new();
+    let mut unreadable = Vec::new();
+    collect_files(dir, &mut files, &mut unreadable)?;
+    files.sort();
+
+    let mut report = RotateReport::default();
+    for (path, e) in unreadable {
+        report.entries.push(RotateEntry {
+            path,
+            size: 0,
+            outcome: RotateOutcome::Failed(e.to_string()),
+        });
+    }
+    for path in files {
+        let meta = match fs::metadata(&path) {
+            Ok(meta) => meta,
+            // 扫描后被删除或无权访问：报告但不中止。
+            Err(e) => {
+                report.entries.push(RotateEntry {
+                    path,
+                    size: 0,
+                    outcome: RotateOutcome::Failed(e.to_string()),
+                });
+                continue;
+            }
+        };
+        if is_compressed(&path) {
+            continue;
+        }
+        match meta.modified() {
+            Ok(mtime) if mtime >= cutoff => continue,
+            Ok(_) => {}
+            // 平台或文件系统不提供 mtime：同样只报告这一个文件。
+            Err(e) => {
+                report.entries.push(RotateEntry {
+                    path,
+                    size: meta.len(),
+                    outcome: RotateOutcome::Failed(e.to_string()),
+                });
+                continue;
+            }
+        }
+        let output = default_output_for_compress(&path);
+        let outcome = if output.exists() {
+            RotateOutcome::Skipped(format!("输出已存在: {}", output.display()))
+        } else if rotate.dry_run {
+            RotateOutcome::WouldCompress { output }
+        } else {
+            match rotate_file(&path, &output, &meta, opts, rotate.keep) {
+                Ok(compressed) => RotateOutcome::Compressed { output, compressed },
+                Err(e) => RotateOutcome::Failed(e.to_string()),
+            }
+        };
+        report.entries.push(RotateEntry {
+            path,
+            size: meta.len(),
+            outcome,
+        });
+    }
+    report.entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(report)
+}
+
+/// 递归收集 `dir` 下的普通文件。只有 `dir` 本身无法读取时报错；无法读取的子目录或条目
+/// 记入 `unreadable`，遍历继续。
+fn collect_files(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    unreadable: &mut Vec<(PathBuf, io::Error)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                unreadable.push((dir.to_path_buf(), e));
+                continue;
+            }
+        };
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => {
+                if let Err(e) = collect_files(&path, files, unreadable) {
+                    unreadable.push((path, e));
+                }
+            }
+            Ok(t) if t.is_file() => files.push(path),
+            Ok(_) => {}
+            Err(e) => unreadable.push((path, e)),
+        }
+    }
+    Ok(())
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        COMPRESSED_SUFFIXES
+            .iter()
+            .any(|s| e.eq_ignore_ascii_case(s))
+    })
+}
+
+/// 压缩单个文件并校验，返回压缩后的大小；成功且未设置 `keep` 时删除原文件。
+/// 失败时删除输出、保留原文件。
+fn rotate_file(
+    input: &Path,
+    output: &Path,
+    meta: &fs::Metadata,
+    opts: &CompressOptions,
+    keep: bool,
+) -> io::Result<u64> {
+    let result = compress_path_with_checksum(input, output, opts, ChecksumAlgorithm::Sha256)
+        .and_then(|expected| {
+            verify_output(output, &expected, opts.buffer_size)?;
+            let now = fs::metadata(input)?;
+            if now.len() != meta.len() || now.modified()? != meta.modified()? {
+                return Err(io::Error::other("压缩期间文件被修改"));
+            }
+            let file = File::options().write(true).open(output)?;
+            file.set_modified(meta.modified()?)?;
+            fs::set_permissions(output, meta.permissions())?;
+            Ok(file.metadata()?.len())
+        });
+    match result {
+        Ok(size) => {
+            if !keep {
+                fs::remove_file(input)?;
+            }
+            Ok(size)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(output);
+            Err(e)
+        }
+    }
+}
+
+/// 重新读取压缩文件并完整解压，核对压缩数据与解压内容的摘要。
+fn verify_output(output: &Path, expected: &Digests, buffer_size: usize) -> io::Result<()> {
+    let mut reader = HashingReader::new(File::open(output)?, Some(expected.algorithm));
+    let mut writer = HashingWriter::new(io::sink(), Some(expected.algorithm));
+    let opts = DecompressOptions {
+        buffer_size,
+        ..Default::default()
+    };
+    decompress_reader_to_writer_with_options(&mut reader, &mut writer, &opts)?;
+    // 解码器可能未读到文件末尾的多余字节，补齐后再计算压缩数据的摘要。
+    io::copy(&mut reader, &mut io::sink())?;
+    let actual = Digests {
+        algorithm: expected.algorithm,
+        compressed: reader.finalize_hex().unwrap_or_default(),
+        uncompressed: writer.finalize_hex().unwrap_or_default(),
+    };
+    verify_digests(expected, &actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompress_path;
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    fn write_aged(path: &Path, data: &[u8], age: Duration) {
+        fs::write(path, data).unwrap();
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("7d"), Ok(7 * DAY));
+        assert_eq!(parse_age("90m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_age("2w"), Ok(14 * DAY));
+        assert!(parse_age("7").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("").is_err());
+    }
+
+    #[test]
+    fn test_rotate_old_files_only() {
+        let dir = std::env::temp_dir().join(format!("rgzip-rotate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let old = dir.join("app.log.1");
+        let nested = dir.join("sub/worker.log");
+        let fresh = dir.join("app.log");
+        let done = dir.join("app.log.2.gz");
+        let blocked = dir.join("blocked.log");
+        write_aged(&old, &b"old line\n".repeat(1000), 10 * DAY);
+        write_aged(&nested, b"nested\n", 30 * DAY);
+        write_aged(&fresh, b"fresh\n", DAY);
+        write_aged(&done, b"not really gzip", 30 * DAY);
+        write_aged(&blocked, b"blocked\n", 30 * DAY);
+        fs::write(dir.join("blocked.log.gz"), b"existing").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&old, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let old_mtime = fs::metadata(&old).unwrap().modified().unwrap();
+        let opts = CompressOptions::default();
+        let mut rotate = RotateOptions {
+            older_than: 7 * DAY,
+            dry_run: true,
+            keep: false,
+        };
+
+        let dry = rotate_dir(&dir, &rotate, &opts).unwrap();
+        assert_eq!((dry.compressed(), dry.skipped(), dry.failed()), (2, 1, 0));
+        assert!(old.exists() && !dir.join("app.log.1.gz").exists());
+
+        rotate.dry_run = false;
+        let report = rotate_dir(&dir, &rotate, &opts).unwrap();
+        let paths: Vec<_> = report.entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, [old.clone(), blocked.clone(), nested.clone()]);
+        assert_eq!((report.compressed(), report.skipped()), (2, 1));
+        assert!(!old.exists() && !nested.exists());
+        assert!(fresh.exists() && blocked.exists() && done.exists());
+
+        let gz = dir.join("app.log.1.gz");
+        let meta = fs::metadata(&gz).unwrap();
+        assert_eq!(meta.modified().unwrap(), old_mtime);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+        }
+        let (before, after) = report.bytes();
+        let nested_gz = fs::metadata(dir.join("sub/worker.log.gz")).unwrap();
+        assert_eq!((before, after), (9007, meta.len() + nested_gz.len()));
+        decompress_path(&gz, &dir.join("check")).unwrap();
+        assert_eq!(
+            fs::read(dir.join("check")).unwrap(),
+            b"old line\n".repeat(1000)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_subdir_reported() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("rgzip-rotate-locked-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let locked = dir.join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        write_aged(&locked.join("hidden.log"), b"hidden\n", 30 * DAY);
+        write_aged(&dir.join("a.log"), b"a\n", 30 * DAY);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        // root 不受目录权限限制，此时无法构造不可读的子目录。
+        let effective = fs::read_dir(&locked).is_err();
+
+        let rotate = RotateOptions {
+            older_than: 7 * DAY,
+            dry_run: false,
+            keep: false,
+        };
+        let report = rotate_dir(&dir, &rotate, &CompressOptions::default());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        let report = report.unwrap();
+        if effective {
+            let paths: Vec<_> = report.entries.iter().map(|e| e.path.clone()).collect();
+            assert_eq!(paths, [dir.join("a.log"), locked.clone()]);
+            assert!(matches!(
+                report.entries[1].outcome,
+                RotateOutcome::Failed(_)
+            ));
+            assert_eq!((report.compressed(), report.failed()), (1, 1));
+        }
+        assert!(dir.join("a.log.gz").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::write::GzEncoder;
+
+use crate::HeaderInfo;
+use crate::{decompress_reader_to_path_with_options, CompressOptions, DecompressOptions};
+use crate::{file_header, gz_encoder, read_full, stream_header};
+
+/// 分卷大小下限：需容纳 gzip 头、结束块与尾部以及足够的数据。
+pub const MIN_SPLIT_SIZE: u64 = 4096;
+
+/// `finish` 写出的结束块与 8 字节尾部所需空间的上限。
+const FINISH_RESERVE: u64 = 16;
+
+/// 每次送入编码器的最大输入块。
+const CHUNK: usize = 64 * 1024;
+
+/// 剩余空间不足以写入这么多输入时结束当前分卷。
+const MIN_PIECE: u64 = 512;
+
+/// 第 `index` 个分卷的路径：`<base>.000`、`<base>.001`……
+pub fn volume_path(base: &Path, index: usize) -> PathBuf {
+    let mut name = base.as_os_str().to_os_string();
+    name.push(format!(".{:03}", index));
+    PathBuf::from(name)
+}
+
+/// 由分卷集合中的任一写法找到全部分卷：`<base>.000` 或（`<base>` 不存在时）`<base>` 本身。
+///
+/// 返回从 `.000` 起连续存在的分卷及基础路径；不是分卷集合时返回 `None`。
+pub fn find_volumes(path: &Path) -> Option<(PathBuf, Vec<PathBuf>)> {
+    let name = path.to_str()?;
+    let base = match name.strip_suffix(".000") {
+        Some(base) if !base.is_empty() => PathBuf::from(base),
+        _ if !path.exists() => path.to_path_buf(),
+        _ => return None,
+    };
+    let volumes: Vec<PathBuf> = (0..)
+        .map(|i| volume_path(&base, i))
+        .take_while(|p| p.is_file())
+        .collect();
+    if volumes.is_empty() {
+        None
+    } else {
+        Some((base, volumes))
+    }
+}
+
+/// 未刷新数据的宽松上限：任何 deflate 编码每字节至多 2 字节（15 位码长），另加块头。
+fn loose_bound(n: u64) -> u64 {
+    2 * n + 2048 * (n / 16384 + 1)
+}
+
+/// 不超过 [`PIECE`] 且刷新后的输入能写入 `space` 字节的最大输入量。
+///
+/// 块完全位于窗口内时，zlib 在压缩结果更大时改用存储块（每块 5 字节开销），
+/// 只用静态 Huffman 的快速级别每字节至多 9 位，因此输出不超过 `n + n/8 + 10`，另加 5 字节刷新标记。
+fn piece_for(space: u64) -> u64 {
+    space.saturating_sub(31) * 8 / 9
+}
+
+/// 接近分卷上限后每次写入并同步刷新的最大输入量，小于 deflate 窗口以保证上述上限成立。
+const PIECE: u64 = 16 * 1024;
+
+/// 统计写出字节数的写入器。
+struct Counter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 将文件压缩为大小不超过 `split_size` 的分卷，每个分卷是完整的 gzip 成员，
+/// 按序拼接即为合法的多成员 gzip 文件。返回生成的分卷路径。
+///
+/// 分卷使用与 [`compress_path_with_options`](crate::compress_path_with_options) 相同的 gzip 头；
+/// `mmap` 与 `dict` 不适用于分卷。
+pub fn compress_path_split(
+    input: &Path,
+    base: &Path,
+    opts: &CompressOptions,
+    split_size: u64,
+) -> io::Result<Vec<PathBuf>> {
+    let file = File::open(input)?;
+    let header = file_header(input, file.metadata().ok().as_ref(), opts);
+    compress_split(file, base, opts, &header, split_size)
+}
+
+/// 将任意读取器的内容压缩为分卷，语义同 [`compress_path_split`]。
+pub fn compress_reader_split<R: Read>(
+    reader: R,
+    base: &Path,
+    opts: &CompressOptions,
+    split_size: u64,
+) -> io::Result<Vec<PathBuf>> {
+    compress_split(reader, base, opts, &stream_header(opts), split_size)
+}
+
+fn compress_split<R: Read>(
+    mut reader: R,
+    base: &Path,
+    opts: &CompressOptions,
+    header: &HeaderInfo,
+    split_size: u64,
+) -> io::Result<Vec<PathBuf>> {
+    if split_size < MIN_SPLIT_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("分卷大小不能小于 {} 字节", MIN_SPLIT_SIZE),
+        ));
+    }
+    if opts.dict.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "预设字典输出不支持分卷",
+        ));
+    }
+    // gzip 头在首次写入时才写出，提前计入。
+    let header_len = 10 + header.filename.as_ref().map_or(0, |n| n.len() as u64 + 1);
+    let limit = split_size - FINISH_RESERVE;
+
+    let mut buf = vec![0u8; CHUNK];
+    let (mut start, mut end) = (0, 0);
+    let mut eof = false;
+    let mut volumes = Vec::new();
+    loop {
+        let path = volume_path(base, volumes.len());
+        let file = BufWriter::with_capacity(opts.buffer_size.max(1), File::create(&path)?);
+        volumes.push(path);
+        let mut encoder = gz_encoder(
+            Counter {
+                inner: file,
+                count: 0,
+            },
+            opts.level,
+            header,
+        );
+        let written = |e: &GzEncoder<Counter<_>>| e.get_ref().count.max(header_len);
+        // 远离上限时整块写入不刷新；自上次同步刷新以来送入的输入字节数。
+        let mut unflushed = 0u64;
+        loop {
+            if start == end && !eof {
+                end = read_full(&mut reader, &mut buf)?;
+                start = 0;
+                eof = end < buf.len();
+            }
+            if start == end {
+                break;
+            }
+            let n = (end - start) as u64;
+            if written(&encoder) + loose_bound(unflushed + n) <= limit {
+                encoder.write_all(&buf[start..end])?;
+                start = end;
+                unflushed += n;
+                continue;
+            }
+            if unflushed > 0 {
+                encoder.flush()?;
+                unflushed = 0;
+            }
+            let piece = n
+                .min(PIECE)
+                .min(piece_for(limit.saturating_sub(written(&encoder))));
+            if piece == 0 || piece < n.min(MIN_PIECE) {
+                break;
+            }
+            let piece = piece as usize;
+            encoder.write_all(&buf[start..start + piece])?;
+            encoder.flush()?;
+            start += piece;
+        }
+        encoder.finish()?.inner.flush()?;
+        if start == end && eof {
+            return Ok(volumes);
+        }
+    }
+}
+
+/// 依次读取多个分卷的读取器。
+pub struct VolumeReader {
+    pending: VecDeque<PathBuf>,
+    current: Option<File>,
+}
+
+impl VolumeReader {
+    pub fn new(volumes: &[PathBuf]) -> Self {
+        VolumeReader {
+            pending: volumes.iter().cloned().collect(),
+            current: None,
+        }
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.current.is_none() {
+                match self.pending.pop_front() {
+                    Some(path) => self.current = Some(File::open(path)?),
+                    None => return Ok(0),
+                }
+            }
+            let n = self.current.as_mut().map_or(Ok(0), |f| f.read(buf))?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            self.current = None;
+        }
+    }
+}
+
+/// 解压分卷集合到指定文件；失败时删除不完整的输出文件。
+pub fn decompress_volumes_to_path(
+    volumes: &[PathBuf],
+    output: &Path,
+    opts: &DecompressOptions,
+) -> io::Result<()> {
+    decompress_reader_to_path_with_options(VolumeReader::new(volumes), output, opts)
+}
+
+/// 删除 `<base>.NNN` 中从 `from` 起连续存在的旧分卷，返回删除的数量。
+pub fn remove_stale_volumes(base: &Path, from: usize) -> io::Result<usize> {
+    let mut removed = 0;
+    while let Ok(()) = fs::remove_file(volume_path(base, from + removed)) {
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompress_reader_to_writer;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rgzip-split-{}-{}", tag, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 可压缩的文本与不可压缩的伪随机数据交替出现。
+    fn mixed(len: usize) -> Vec<u8> {
+        let mut state = 0x9e37_79b9u32;
+        (0..len)
+            .map(|i| {
+                if (i / 50_000) % 2 == 0 {
+                    b"split volume test line\n"[i % 23]
+                } else {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as u8
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_volumes_bounded_and_concatenate() {
+        let dir = temp_dir("bounded");
+        let data = mixed(600_000);
+        for (level, split_size) in [(0, 64 * 1024), (6, 50_000), (9, MIN_SPLIT_SIZE)] {
+            let base = dir.join(format!("data-{}.gz", level));
+            let opts = CompressOptions::with_level(level);
+            let volumes = compress_reader_split(&data[..], &base, &opts, split_size).unwrap();
+            assert!(volumes.len() > 1);
+
+            let mut joined = Vec::new();
+            for (i, v) in volumes.iter().enumerate() {
+                let bytes = fs::read(v).unwrap();
+                assert!(
+                    bytes.len() as u64 <= split_size,
+                    "{} 超出分卷大小: {}",
+                    v.display(),
+                    bytes.len()
+                );
+                // 除最后一卷外应接近上限，避免产生过多分卷。
+                if i + 1 < volumes.len() {
+                    assert!(bytes.len() as u64 > split_size - 1024);
+                }
+                let mut plain = Vec::new();
+                decompress_reader_to_writer(&bytes[..], &mut plain).unwrap();
+                joined.extend(bytes);
+            }
+            let mut out = Vec::new();
+            decompress_reader_to_writer(&joined[..], &mut out).unwrap();
+            assert_eq!(out, data);
+
+            let (found_base, found) = find_volumes(&volumes[0]).unwrap();
+            assert_eq!((found_base, &found), (base.clone(), &volumes));
+            let output = dir.join(format!("out-{}", level));
+            decompress_volumes_to_path(&found, &output, &DecompressOptions::default()).unwrap();
+            assert_eq!(fs::read(&output).unwrap(), data);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_empty_input_and_stale_volumes() {
+        let dir = temp_dir("empty");
+        let base = dir.join("e.gz");
+        for i in 0..3 {
+            fs::write(volume_path(&base, i), b"old").unwrap();
+        }
+        let volumes =
+            compress_reader_split(&b""[..], &base, &CompressOptions::default(), MIN_SPLIT_SIZE)
+                .unwrap();
+        assert_eq!(volumes, [dir.join("e.gz.000")]);
+        assert_eq!(remove_stale_volumes(&base, volumes.len()).unwrap(), 2);
+        assert_eq!(find_volumes(&base).unwrap().1, volumes);
+        assert!(find_volumes(&dir.join("missing.gz")).is_none());
+
+        let opts = CompressOptions::default();
+        assert!(compress_reader_split(&b"x"[..], &base, &opts, 100).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,760 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::decompress_reader_to_writer;
+
+const BLOCK: usize = 512;
+/// pax 扩展头与 GNU 长文件名记录的长度上限，防止恶意归档耗尽内存。
+const MAX_META_LEN: u64 = 1 << 20;
+
+/// tar 成员类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    Symlink,
+    Hardlink,
+    Other(u8),
+}
+
+impl EntryKind {
+    fn from_flag(flag: u8) -> Self {
+        match flag {
+            b'0' | 0 | b'7' => EntryKind::File,
+            b'5' => EntryKind::Dir,
+            b'2' => EntryKind::Symlink,
+            b'1' => EntryKind::Hardlink,
+            other => EntryKind::Other(other),
+        }
+    }
+
+    /// `ls -l` 风格的类型字符。
+    pub fn as_char(&self) -> char {
+        match self {
+            EntryKind::File => '-',
+            EntryKind::Dir => 'd',
+            EntryKind::Symlink => 'l',
+            EntryKind::Hardlink => 'h',
+            EntryKind::Other(_) => '?',
+        }
+    }
+}
+
+/// 一个 tar 成员的元数据（已合并 pax / GNU 长文件名扩展）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarEntry {
+    pub path: String,
+    pub kind: EntryKind,
+    pub size: u64,
+    pub mode: u32,
+    pub mtime: u64,
+    pub link_name: Option<String>,
+}
+
+impl TarEntry {
+    /// `-rw-r--r--` 形式的权限字符串。
+    pub fn mode_string(&self) -> String {
+        let mut s = String::with_capacity(10);
+        s.push(self.kind.as_char());
+        for shift in [6, 3, 0] {
+            let bits = (self.mode >> shift) & 0o7;
+            s.push(if bits & 4 != 0 { 'r' } else { '-' });
+            s.push(if bits & 2 != 0 { 'w' } else { '-' });
+            s.push(if bits & 1 != 0 { 'x' } else { '-' });
+        }
+        s
+    }
+}
+
+/// 接收解析出的 tar 成员及其数据。
+pub trait TarVisitor {
+    fn start(&mut self, entry: &TarEntry) -> io::Result<()>;
+    fn data(&mut self, chunk: &[u8]) -> io::Result<()>;
+    fn end(&mut self) -> io::Result<()>;
+}
+
+enum Payload {
+    Entry,
+    Pax(Vec<u8>),
+    GlobalPax(Vec<u8>),
+    LongName(Vec<u8>),
+    LongLink(Vec<u8>),
+}
+
+enum State {
+    Header,
+    Data {
+        remaining: u64,
+        pad: usize,
+        payload: Payload,
+    },
+    Padding(usize),
+    End,
+}
+
+/// 以 `Write` 形式接收解压后字节流的 ustar/pax 解析器，可直接作为
+/// [`decompress_reader_to_writer`] 的输出，全程流式、不缓存成员数据。
+pub struct TarSink<V: TarVisitor> {
+    visitor: V,
+    state: State,
+    block: Vec<u8>,
+    pax: Vec<(String, String)>,
+    global_pax: Vec<(String, String)>,
+    long_name: Option<String>,
+    long_link: Option<String>,
+}
+
+impl<V: TarVisitor> TarSink<V> {
+    pub fn new(visitor: V) -> Self {
+        TarSink {
+            visitor,
+            state: State::Header,
+            block: Vec::with_capacity(BLOCK),
+            pax: Vec::new(),
+            global_pax: Vec::new(),
+            long_name: None,
+            long_link: None,
+        }
+    }
+
+    /// 检查归档是否完整结束，并取回访问者。
+    pub fn finish(self) -> io::Result<V> {
+        match self.state {
+            State::End => Ok(self.visitor),
+            State::Header if self.block.is_empty() => Ok(self.visitor),
+            _ => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "tar 归档被截断",
+            )),
+        }
+    }
+
+    fn on_header(&mut self) -> io::Result<()> {
+        let header = std::mem::take(&mut self.block);
+        if header.iter().all(|&b| b == 0) {
+            self.state = State::End;
+            return Ok(());
+        }
+        verify_checksum(&header)?;
+
+        let flag = header[156];
+        let mut size = parse_numeric(&header[124..136])?;
+        let payload = match flag {
+            b'x' => Payload::Pax(Vec::new()),
+            b'g' => Payload::GlobalPax(Vec::new()),
+            b'L' => Payload::LongName(Vec::new()),
+            b'K' => Payload::LongLink(Vec::new()),
+            _ => {
+                let mut entry = self.build_entry(&header, flag, size)?;
+                if matches!(
+                    entry.kind,
+                    EntryKind::Dir | EntryKind::Symlink | EntryKind::Hardlink
+                ) {
+                    entry.size = 0;
+                }
+                size = entry.size;
+                self.visitor.start(&entry)?;
+                Payload::Entry
+            }
+        };
+        if !matches!(payload, Payload::Entry) && size > MAX_META_LEN {
+            return Err(invalid("tar 扩展头过大"));
+        }
+        let pad = ((BLOCK as u64 - size % BLOCK as u64) % BLOCK as u64) as usize;
+        self.state = State::Data {
+            remaining: size,
+            pad,
+            payload,
+        };
+        self.maybe_finish_data()
+    }
+
+    fn build_entry(&mut self, header: &[u8], flag: u8, size: u64) -> io::Result<TarEntry> {
+        let mut path = cstr(&header[0..100]);
+        if &header[257..262] == b"ustar" && header[262] == 0 {
+            let prefix = cstr(&header[345..500]);
+            if !prefix.is_empty() {
+                path = format!("{}/{}", prefix, path);
+            }
+        }
+        let link = cstr(&header[157..257]);
+        let mut entry = TarEntry {
+            path,
+            kind: EntryKind::from_flag(flag),
+            size,
+            mode: parse_numeric(&header[100..108])? as u32,
+            mtime: parse_numeric(&header[136..148])?,
+            link_name: if link.is_empty() { None } else { Some(link) },
+        };
+        if let Some(name) = self.long_name.take() {
+            entry.path = name;
+        }
+        if let Some(link) = self.long_link.take() {
+            entry.link_name = Some(link);
+        }
+        let pax = std::mem::take(&mut self.pax);
+        for (key, value) in self.global_pax.iter().chain(pax.iter()) {
+            match key.as_str() {
+                "path" => entry.path = value.clone(),
+                "linkpath" => entry.link_name = Some(value.clone()),
+                "size" => entry.size = value.parse().map_err(|_| invalid("pax size 无效"))?,
+                "mtime" => {
+                    let secs = value.split('.').next().unwrap_or("0");
+                    entry.mtime = secs.parse().map_err(|_| invalid("pax mtime 无效"))?;
+                }
+                _ => {}
+            }
+        }
+        if entry.kind == EntryKind::File && entry.path.ends_with('/') {
+            entry.kind = EntryKind::Dir;
+        }
+        Ok(entry)
+    }
+
+    fn maybe_finish_data(&mut self) -> io::Result<()> {
+        let (pad, payload) = match &mut self.state {
+            State::Data {
+                remaining: 0,
+                pad,
+                payload,
+            } => (*pad, std::mem::replace(payload, Payload::Entry)),
+            _ => return Ok(()),
+        };
+        match payload {
+            Payload::Entry => self.visitor.end()?,
+            Payload::Pax(data) => self.pax = parse_pax(&data)?,
+            Payload::GlobalPax(data) => self.global_pax.extend(parse_pax(&data)?),
+            Payload::LongName(data) => self.long_name = Some(cstr(&data)),
+            Payload::LongLink(data) => self.long_link = Some(cstr(&data)),
+        }
+        self.state = if pad == 0 {
+            State::Header
+        } else {
+            State::Padding(pad)
+        };
+        Ok(())
+    }
+}
+
+impl<V: TarVisitor> Write for TarSink<V> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            match &mut self.state {
+                State::End => break,
+                State::Header => {
+                    let take = (BLOCK - self.block.len()).min(rest.len());
+                    self.block.extend_from_slice(&rest[..take]);
+                    rest = &rest[take..];
+                    if self.block.len() == BLOCK {
+                        self.on_header()?;
+                    }
+                }
+                State::Padding(pad) => {
+                    let take = (*pad).min(rest.len());
+                    *pad -= take;
+                    rest = &rest[take..];
+                    if *pad == 0 {
+                        self.state = State::Header;
+                    }
+                }
+                State::Data {
+                    remaining, payload, ..
+                } => {
+                    let take = (*remaining).min(rest.len() as u64) as usize;
+                    let chunk = &rest[..take];
+                    match payload {
+                        Payload::Entry => self.visitor.data(chunk)?,
+                        Payload::Pax(v)
+                        | Payload::GlobalPax(v)
+                        | Payload::LongName(v)
+                        | Payload::LongLink(v) => v.extend_from_slice(chunk),
+                    }
+                    *remaining -= take as u64;
+                    rest = &rest[take..];
+                    self.maybe_finish_data()?;
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn cstr(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// 解析八进制数字段；最高位置 1 时按 GNU base-256 编码解析。
+fn parse_numeric(field: &[u8]) -> io::Result<u64> {
+    if field.first().is_some_and(|&b| b & 0x80 != 0) {
+        let mut value: u64 = (field[0] & 0x7f) as u64;
+        for &b in &field[1..] {
+            value = value
+                .checked_mul(256)
+                .and_then(|v| v.checked_add(b as u64))
+                .ok_or_else(|| invalid("tar 数字字段溢出"))?;
+        }
+        return Ok(value);
+    }
+    let text = cstr(field);
+    let text = text.trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).map_err(|_| invalid("tar 数字字段无效"))
+}
+
+fn verify_checksum(header: &[u8]) -> io::Result<()> {
+    let expected = parse_numeric(&header[148..156])?;
+    let actual: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| {
+            if (148..156).contains(&i) {
+                b' ' as u64
+            } else {
+                b as u64
+            }
+        })
+        .sum();
+    if expected != actual {
+        return Err(invalid("tar 头校验和不匹配（不是 tar 归档？）"));
+    }
+    Ok(())
+}
+
+/// 解析 pax 记录：`<len> <key>=<value>\n`。
+fn parse_pax(data: &[u8]) -> io::Result<Vec<(String, String)>> {
+    let mut out = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest
+            .iter()
+            .position(|&b| b == b' ')
+            .ok_or_else(|| invalid("pax 记录无效"))?;
+        let len: usize = std::str::from_utf8(&rest[..space])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n| n > space + 1 && n <= rest.len())
+            .ok_or_else(|| invalid("pax 记录长度无效"))?;
+        let record = &rest[space + 1..len - 1];
+        if let Some(eq) = record.iter().position(|&b| b == b'=') {
+            let key = String::from_utf8_lossy(&record[..eq]).into_owned();
+            let value = String::from_utf8_lossy(&record[eq + 1..]).into_owned();
+            out.push((key, value));
+        }
+        rest = &rest[len..];
+    }
+    Ok(out)
+}
+
+/// 将归档内的相对路径安全地拼接到 `root` 下；绝对路径或包含 `..` 时返回 `None`。
+pub fn safe_join(root: &Path, member: &str) -> Option<PathBuf> {
+    let mut out = root.to_path_buf();
+    let mut depth = 0usize;
+    for comp in Path::new(member).components() {
+        match comp {
+            Component::Normal(part) => {
+                out.push(part);
+                depth += 1;
+            }
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if depth == 0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// 按字面规整相对路径，判断其是否始终停留在根目录之内（允许中途出现 `..`）。
+fn stays_within(path: &Path) -> bool {
+    let mut depth = 0usize;
+    for comp in path.components() {
+        match comp {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// 确认 `path` 在 `root` 之下的已存在祖先都不是符号链接，防止借链接逃逸出目标目录。
+fn ensure_no_symlink_ancestors(root: &Path, path: &Path) -> io::Result<()> {
+    let rel = path
+        .strip_prefix(root)
+        .map_err(|_| invalid("路径不在目标目录内"))?;
+    let mut cur = root.to_path_buf();
+    let mut comps = rel.components().peekable();
+    while let Some(comp) = comps.next() {
+        if comps.peek().is_none() {
+            break;
+        }
+        cur.push(comp);
+        if let Ok(meta) = fs::symlink_metadata(&cur) {
+            if meta.file_type().is_symlink() {
+                return Err(invalid(&format!("拒绝经由符号链接写入: {}", cur.display())));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remove_existing(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => Ok(()),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+struct Lister {
+    entries: Vec<TarEntry>,
+}
+
+impl TarVisitor for Lister {
+    fn start(&mut self, entry: &TarEntry) -> io::Result<()> {
+        self.entries.push(entry.clone());
+        Ok(())
+    }
+
+    fn data(&mut self, _chunk: &[u8]) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Extractor {
+    root: PathBuf,
+    entries: Vec<TarEntry>,
+    current: Option<(File, TarEntry)>,
+    /// 目录权限在全部成员写完后再设置，避免只读目录阻止后续写入。
+    dirs: Vec<(PathBuf, u32)>,
+}
+
+impl Extractor {
+    fn target(&self, member: &str) -> io::Result<PathBuf> {
+        let path = safe_join(&self.root, member)
+            .ok_or_else(|| invalid(&format!("拒绝不安全的成员路径: {}", member)))?;
+        ensure_no_symlink_ancestors(&self.root, &path)?;
+        Ok(path)
+    }
+}
+
+impl TarVisitor for Extractor {
+    fn start(&mut self, entry: &TarEntry) -> io::Result<()> {
+        let path = self.target(&entry.path)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match entry.kind {
+            EntryKind::Dir => {
+                // 已存在的符号链接替换为真实目录（同 GNU tar），不沿链接在目标目录之外建目录、改权限。
+                if fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink()) {
+                    remove_symlink(&path)?;
+                }
+                fs::create_dir_all(&path)?;
+                self.dirs.push((path, entry.mode));
+            }
+            EntryKind::File => {
+                remove_existing(&path)?;
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)?;
+                self.current = Some((file, entry.clone()));
+            }
+            EntryKind::Hardlink => {
+                let link = entry.link_name.as_deref().unwrap_or("");
+                let source = self.target(link)?;
+                remove_existing(&path)?;
+                fs::hard_link(source, &path)?;
+            }
+            EntryKind::Symlink => {
+                let link = entry.link_name.as_deref().unwrap_or("");
+                let base = Path::new(&entry.path).parent().unwrap_or(Path::new(""));
+                if !stays_within(&base.join(link)) {
+                    return Err(invalid(&format!(
+                        "拒绝指向目标目录之外的符号链接: {} -> {}",
+                        entry.path, link
+                    )));
+                }
+                remove_existing(&path)?;
+                make_symlink(link, &path)?;
+            }
+            EntryKind::Other(flag) => {
+                eprintln!(
+                    "提示: 跳过不支持的成员类型 '{}': {}",
+                    flag as char, entry.path
+                );
+                return Ok(());
+            }
+        }
+        self.entries.push(entry.clone());
+        Ok(())
+    }
+
+    fn data(&mut self, chunk: &[u8]) -> io::Result<()> {
+        if let Some((file, _)) = self.current.as_mut() {
+            file.write_all(chunk)?;
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        if let Some((file, entry)) = self.current.take() {
+            file.set_modified(UNIX_EPOCH + Duration::from_secs(entry.mtime))?;
+            // 经已打开的句柄设置权限，不再按路径查找。
+            if let Some(perm) = permissions(entry.mode) {
+                file.set_permissions(perm)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 成员的权限位；非 Unix 平台不设置权限。
+#[cfg(unix)]
+fn permissions(mode: u32) -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(fs::Permissions::from_mode(mode & 0o777))
+}
+
+#[cfg(not(unix))]
+fn permissions(_mode: u32) -> Option<fs::Permissions> {
+    None
+}
+
+/// 设置解包出的目录的权限；`path` 已不是目录（如被替换为符号链接）时报错，不跟随链接。
+fn set_dir_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let Some(perm) = permissions(mode) else {
+        return Ok(());
+    };
+    if !fs::symlink_metadata(path)?.is_dir() {
+        return Err(invalid(&format!(
+            "拒绝修改非目录的权限: {}",
+            path.display()
+        )));
+    }
+    fs::set_permissions(path, perm)
+}
+
+/// 删除符号链接本身；Windows 上指向目录的链接需用 `remove_dir` 删除。
+fn remove_symlink(path: &Path) -> io::Result<()> {
+    fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &str, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(not(unix))]
+fn make_symlink(target: &str, path: &Path) -> io::Result<()> {
+    eprintln!(
+        "提示: 当前平台跳过符号链接: {} -> {}",
+        path.display(),
+        target
+    );
+    Ok(())
+}
+
+/// 列出 `.tar.gz` 流中的全部成员。
+pub fn list_tar_gz<R: Read>(reader: R) -> io::Result<Vec<TarEntry>> {
+    let mut sink = TarSink::new(Lister {
+        entries: Vec::new(),
+    });
+    decompress_reader_to_writer(reader, &mut sink)?;
+    Ok(sink.finish()?.entries)
+}
+
+/// 将 `.tar.gz` 流解包到 `dest` 目录，拒绝绝对路径、`..` 以及经由符号链接的写入。
+pub fn extract_tar_gz<R: Read>(reader: R, dest: &Path) -> io::Result<Vec<TarEntry>> {
+    fs::create_dir_all(dest)?;
+    let mut sink = TarSink::new(Extractor {
+        root: dest.to_path_buf(),
+        entries: Vec::new(),
+        current: None,
+        dirs: Vec::new(),
+    });
+    decompress_reader_to_writer(reader, &mut sink)?;
+    let extractor = sink.finish()?;
+    for (dir, mode) in extractor.dirs.iter().rev() {
+        set_dir_mode(dir, *mode)?;
+    }
+    Ok(extractor.entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress_reader_to_writer;
+
+    fn header(name: &str, flag: u8, size: usize, link: &str) -> Vec<u8> {
+        let mut h = vec![0u8; BLOCK];
+        h[..name.len()].copy_from_slice(name.as_bytes());
+        h[100..107].copy_from_slice(b"0000644");
+        h[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+        h[136..147].copy_from_slice(b"00000000001");
+        h[156] = flag;
+        h[157..157 + link.len()].copy_from_slice(link.as_bytes());
+        h[257..263].copy_from_slice(b"ustar\0");
+        h[263..265].copy_from_slice(b"00");
+        h[148..156].copy_from_slice(b"        ");
+        let sum: u32 = h.iter().map(|&b| b as u32).sum();
+        h[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+        h
+    }
+
+    fn member(out: &mut Vec<u8>, name: &str, flag: u8, data: &[u8], link: &str) {
+        out.extend(header(name, flag, data.len(), link));
+        out.extend_from_slice(data);
+        out.resize(out.len().div_ceil(BLOCK) * BLOCK, 0);
+    }
+
+    fn archive(members: &[(&str, u8, &[u8], &str)]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (name, flag, data, link) in members {
+            member(&mut tar, name, *flag, data, link);
+        }
+        tar.extend(vec![0u8; BLOCK * 2]);
+        compress_reader_to_writer(&tar[..], Vec::new(), 6).unwrap()
+    }
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rgzip-tar-{}-{}", tag, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_list_members() {
+        let gz = archive(&[("dir/", b'5', b"", ""), ("dir/a.txt", b'0', b"hello", "")]);
+        let entries = list_tar_gz(&gz[..]).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, EntryKind::Dir);
+        assert_eq!(entries[1].path, "dir/a.txt");
+        assert_eq!(entries[1].size, 5);
+        assert_eq!(entries[1].mode_string(), "-rw-r--r--");
+    }
+
+    #[test]
+    fn test_pax_and_gnu_long_names() {
+        let long = "x".repeat(150);
+        let pax_record = format!("{} path={}\n", long.len() + 10, long);
+        let gnu_name = format!("{}\0", "y".repeat(120));
+        let gz = archive(&[
+            ("PaxHeader", b'x', pax_record.as_bytes(), ""),
+            ("short", b'0', b"1", ""),
+            ("././@LongLink", b'L', gnu_name.as_bytes(), ""),
+            ("trunc", b'0', b"2", ""),
+        ]);
+        let entries = list_tar_gz(&gz[..]).unwrap();
+        assert_eq!(entries[0].path, long);
+        assert_eq!(entries[1].path, "y".repeat(120));
+    }
+
+    #[test]
+    fn test_extract_roundtrip() {
+        let dir = temp_dir("extract");
+        let gz = archive(&[("d/", b'5', b"", ""), ("d/f.txt", b'0', b"content", "")]);
+        extract_tar_gz(&gz[..], &dir).unwrap();
+        assert_eq!(fs::read(dir.join("d/f.txt")).unwrap(), b"content");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_traversal() {
+        assert!(safe_join(Path::new("/out"), "../etc/passwd").is_none());
+        assert!(safe_join(Path::new("/out"), "/etc/passwd").is_none());
+        assert!(safe_join(Path::new("/out"), "a/../../b").is_none());
+        assert_eq!(
+            safe_join(Path::new("/out"), "./a/b"),
+            Some(PathBuf::from("/out/a/b"))
+        );
+
+        let dir = temp_dir("traversal");
+        let gz = archive(&[("../evil.txt", b'0', b"x", "")]);
+        let err = extract_tar_gz(&gz[..], &dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(!dir.parent().unwrap().join("evil.txt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_write_through_symlink() {
+        let dir = temp_dir("symlink");
+        let gz = archive(&[("link", b'2', b"", "/tmp"), ("link/evil", b'0', b"x", "")]);
+        assert!(extract_tar_gz(&gz[..], &dir).is_err());
+
+        let gz = archive(&[("sub/", b'5', b"", ""), ("sub/up", b'2', b"", "../sub")]);
+        extract_tar_gz(&gz[..], &dir).unwrap();
+        let gz = archive(&[("sub/up/x", b'0', b"x", "")]);
+        assert!(extract_tar_gz(&gz[..], &dir).is_err());
+        assert!(!stays_within(Path::new("sub/../..")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dir_entry_replaces_existing_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("dir-over-link");
+        let outside = temp_dir("dir-over-link-outside");
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::set_permissions(&outside, fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink(&outside, dir.join("d")).unwrap();
+
+        let gz = archive(&[("d/", b'5', b"", ""), ("d/f.txt", b'0', b"x", "")]);
+        extract_tar_gz(&gz[..], &dir).unwrap();
+        let meta = fs::symlink_metadata(dir.join("d")).unwrap();
+        assert!(meta.is_dir(), "符号链接应被替换为目录");
+        assert_eq!(meta.permissions().mode() & 0o777, 0o644);
+        let outside_meta = fs::metadata(&outside).unwrap();
+        assert_eq!(outside_meta.permissions().mode() & 0o777, 0o755);
+        assert!(!outside.join("f.txt").exists());
+        // 设置目录权限时不跟随符号链接。
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        assert!(set_dir_mode(&link, 0o700).is_err());
+        let outside_meta = fs::metadata(&outside).unwrap();
+        assert_eq!(outside_meta.permissions().mode() & 0o777, 0o755);
+        fs::set_permissions(dir.join("d"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_archive() {
+        let mut tar = Vec::new();
+        member(&mut tar, "a", b'0', &[7u8; 1000], "");
+        tar.truncate(700);
+        let gz = compress_reader_to_writer(&tar[..], Vec::new(), 6).unwrap();
+        let err = list_tar_gz(&gz[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,220 @@
+//! gzip-go 与 rgzip 的一致性测试：用例记录在 `gzip/conformance/cases.toml`。
+//!
+//! 默认只检查 rgzip；设置 `GZIP_GO_BIN` 指向已构建的 gzip-go 时同时检查 Go 实现
+//! （`make -C gzip/go conformance`）。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use rgzip::{decompress_reader_to_writer, read_gz_header};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Suite {
+    case: Vec<Case>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Case {
+    name: String,
+    /// 工作目录中的文件名 -> fixtures/ 中的文件名。
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+    args: Vec<String>,
+    /// 作为标准输入的 fixture。
+    stdin: Option<String>,
+    exit: i32,
+    /// 标准错误中应包含的文本。
+    stderr: Option<String>,
+    #[serde(default)]
+    exists: Vec<String>,
+    #[serde(default)]
+    absent: Vec<String>,
+    /// 工作目录中的文件内容应与 fixture 相同。
+    #[serde(default)]
+    same: BTreeMap<String, String>,
+    /// 工作目录中的 gzip 文件解压后应与 fixture 相同。
+    #[serde(default)]
+    gunzip: BTreeMap<String, String>,
+    /// gzip 头中记录的文件名。
+    #[serde(default)]
+    gz_name: BTreeMap<String, String>,
+    /// 文件的修改时间（Unix 秒）。
+    #[serde(default)]
+    mtime: BTreeMap<String, u64>,
+    stdout: Option<String>,
+    stdout_gunzip: Option<String>,
+}
+
+struct Implementation {
+    name: &'static str,
+    bin: PathBuf,
+}
+
+fn conformance_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../conformance")
+}
+
+fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    decompress_reader_to_writer(data, &mut out).map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// 运行一个用例，返回全部不符合预期之处。
+fn run_case(imp: &Implementation, case: &Case, fixtures: &Path) -> Vec<String> {
+    let work = std::env::temp_dir().join(format!(
+        "rgzip-conformance-{}-{}-{}",
+        imp.name,
+        case.name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&work);
+    fs::create_dir_all(&work).unwrap();
+    for (name, fixture) in &case.files {
+        let dest = work.join(name);
+        fs::create_dir_all(dest.parent().unwrap()).unwrap();
+        fs::copy(fixtures.join(fixture), dest).unwrap();
+    }
+
+    // 隔离用户环境：默认选项环境变量与配置文件都会改变 rgzip 的行为。
+    let mut child = Command::new(&imp.bin)
+        .args(&case.args)
+        .current_dir(&work)
+        .env_remove("GZIP")
+        .env_remove("RGZIP")
+        .env_remove("RGZIP_CONFIG")
+        .env_remove("SOURCE_DATE_EPOCH")
+        .env("HOME", &work)
+        .env("XDG_CONFIG_HOME", work.join(".config"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("无法运行 {}: {}", imp.bin.display(), e));
+    let stdin_data = case
+        .stdin
+        .as_ref()
+        .map(|f| fs::read(fixtures.join(f)).unwrap())
+        .unwrap_or_default();
+    child.stdin.take().unwrap().write_all(&stdin_data).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let mut problems = Vec::new();
+    let mut check = |ok: bool, msg: String| {
+        if !ok {
+            problems.push(msg);
+        }
+    };
+    check(
+        output.status.code() == Some(case.exit),
+        format!("退出码 {:?}，期望 {}", output.status.code(), case.exit),
+    );
+    if let Some(expected) = &case.stderr {
+        check(
+            stderr.contains(expected.as_str()),
+            format!("stderr 不含 {:?}", expected),
+        );
+    }
+    for name in &case.exists {
+        check(work.join(name).exists(), format!("{} 应存在", name));
+    }
+    for name in &case.absent {
+        check(!work.join(name).exists(), format!("{} 不应存在", name));
+    }
+    let fixture = |f: &String| fs::read(fixtures.join(f)).unwrap();
+    for (name, f) in &case.same {
+        let actual = fs::read(work.join(name)).ok();
+        check(
+            actual.as_ref() == Some(&fixture(f)),
+            format!("{} 应与 fixture {} 相同", name, f),
+        );
+    }
+    for (name, f) in &case.gunzip {
+        let actual = fs::read(work.join(name)).map_err(|e| e.to_string());
+        let plain = actual.and_then(|d| gunzip(&d));
+        check(
+            plain.as_ref() == Ok(&fixture(f)),
+            format!("{} 解压后应与 fixture {} 相同: {:?}", name, f, plain.err()),
+        );
+    }
+    for (name, expected) in &case.gz_name {
+        let header = fs::File::open(work.join(name)).and_then(read_gz_header);
+        let actual = header.ok().and_then(|h| h.filename_lossy());
+        check(
+            actual.as_deref() == Some(expected.as_str()),
+            format!("{} 头部文件名 {:?}，期望 {:?}", name, actual, expected),
+        );
+    }
+    for (name, expected) in &case.mtime {
+        let actual = fs::metadata(work.join(name))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        check(
+            actual == Some(*expected),
+            format!("{} mtime {:?}，期望 {}", name, actual, expected),
+        );
+    }
+    if let Some(f) = &case.stdout {
+        check(
+            output.stdout == fixture(f),
+            format!("stdout 应与 fixture {} 相同", f),
+        );
+    }
+    if let Some(f) = &case.stdout_gunzip {
+        check(
+            gunzip(&output.stdout).as_ref() == Ok(&fixture(f)),
+            format!("stdout 解压后应与 fixture {} 相同", f),
+        );
+    }
+    if !problems.is_empty() {
+        problems.push(format!("stderr: {}", stderr.trim_end()));
+    }
+    let _ = fs::remove_dir_all(&work);
+    problems
+}
+
+fn run_suite(imp: Implementation) {
+    let dir = conformance_dir();
+    let text = fs::read_to_string(dir.join("cases.toml")).unwrap();
+    let suite: Suite = toml::from_str(&text).unwrap();
+    let fixtures = dir.join("fixtures");
+    let failures: Vec<String> = suite
+        .case
+        .iter()
+        .filter_map(|case| {
+            let problems = run_case(&imp, case, &fixtures);
+            (!problems.is_empty())
+                .then(|| format!("[{}] {}:\n  {}", imp.name, case.name, problems.join("\n  ")))
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_conformance_rust() {
+    run_suite(Implementation {
+        name: "rust",
+        bin: PathBuf::from(env!("CARGO_BIN_EXE_rgzip")),
+    });
+}
+
+#[test]
+fn test_conformance_go() {
+    let Some(bin) = std::env::var_os("GZIP_GO_BIN") else {
+        eprintln!("未设置 GZIP_GO_BIN，跳过 gzip-go 一致性测试");
+        return;
+    };
+    run_suite(Implementation {
+        name: "go",
+        bin: PathBuf::from(bin),
+    });
+}
@@ -0,0 +1,74 @@
+//! 畸形样本回归测试：期望结果记录在 `tests/data/malformed/expected.txt`。
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use rgzip::{decompress_reader_to_writer, read_gz_header};
+
+fn kind_name(kind: ErrorKind) -> String {
+    format!("{:?}", kind)
+}
+
+/// 一个畸形样本及其期望结果。
+struct Case {
+    name: String,
+    data: Vec<u8>,
+    decompress: String,
+    header: String,
+}
+
+/// 读取 `expected.txt` 中的全部样本，并确认目录下的样本都已登记。
+fn cases() -> Vec<Case> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/malformed");
+    let expected = fs::read_to_string(dir.join("expected.txt")).unwrap();
+    let mut cases = Vec::new();
+    for line in expected.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [name, decompress, header] = fields[..] else {
+            panic!("expected.txt 格式错误: {}", line);
+        };
+        cases.push(Case {
+            name: name.to_string(),
+            data: fs::read(dir.join(name)).unwrap(),
+            decompress: decompress.to_string(),
+            header: header.to_string(),
+        });
+    }
+    let files = fs::read_dir(&dir).unwrap().count() - 1;
+    assert_eq!(cases.len(), files, "expected.txt 未覆盖全部样本");
+    cases
+}
+
+#[test]
+fn test_malformed_corpus() {
+    for case in cases() {
+        let name = &case.name;
+        let err = decompress_reader_to_writer(&case.data[..], Vec::new())
+            .expect_err(&format!("{} 应当解压失败", name));
+        assert_eq!(kind_name(err.kind()), case.decompress, "{}: {}", name, err);
+
+        match read_gz_header(&case.data[..]) {
+            Ok(_) => assert_eq!(case.header, "ok", "{}: 头部不应解析成功", name),
+            Err(e) => assert_eq!(kind_name(e.kind()), case.header, "{}: {}", name, e),
+        }
+    }
+}
+
+/// 异步接口与同步接口共用解码器，错误类型必须与 `expected.txt` 一致。
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_malformed_corpus_async() {
+    for case in cases() {
+        let name = &case.name;
+        let mut out = Vec::new();
+        let err = rgzip::decompress_reader_to_writer_async(&case.data[..], &mut out)
+            .await
+            .expect_err(&format!("{} 应当解压失败", name));
+        assert_eq!(kind_name(err.kind()), case.decompress, "{}: {}", name, err);
+    }
+}
@@ -0,0 +1,126 @@
+//! 可复现模式：相同内容在不同目录、mtime、缓冲区配置与读取分块下得到相同字节，
+//! 且输出哈希固定（跨机器一致；更换压缩后端等导致哈希变化时需确认后更新）。
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use rgzip::{
+    compress_path_with_options, compress_reader_to_writer_with_options, read_gz_header,
+    CompressOptions, OS_UNKNOWN,
+};
+use sha2::{Digest, Sha256};
+
+const EPOCH: u32 = 1_700_000_000;
+
+/// 约 1 MiB 的确定性类文本内容。
+fn content() -> Vec<u8> {
+    let mut state = 0x2545_f491u32;
+    let mut out = Vec::with_capacity(1 << 20);
+    while out.len() < 1 << 20 {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let line = format!(
+            "{} level={} module=svc{} msg=\"request {} done\"\n",
+            state >> 8,
+            ["INFO", "WARN", "DEBUG"][(state % 3) as usize],
+            state % 17,
+            state % 1000
+        );
+        out.extend_from_slice(line.as_bytes());
+    }
+    out
+}
+
+fn hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 每次只返回 1~7 字节的读取器，模拟管道的短读。
+struct ShortReads<'a>(&'a [u8], usize);
+
+impl Read for ShortReads<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.1 = self.1 % 7 + 1;
+        let n = self.1.min(buf.len()).min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+fn write_input(dir: &Path, data: &[u8], mtime: u64) -> PathBuf {
+    fs::create_dir_all(dir).unwrap();
+    let path = dir.join("data.log");
+    fs::write(&path, data).unwrap();
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(UNIX_EPOCH + Duration::from_secs(mtime))
+        .unwrap();
+    path
+}
+
+#[test]
+fn test_reproducible_path_output() {
+    let data = content();
+    let root = std::env::temp_dir().join(format!("rgzip-repro-{}", std::process::id()));
+    let inputs = [
+        write_input(&root.join("a"), &data, EPOCH as u64 + 86_400),
+        write_input(&root.join("b/c"), &data, EPOCH as u64 + 3),
+    ];
+
+    let mut outputs = Vec::new();
+    for (input, buffer_size, mmap) in [
+        (&inputs[0], 1, false),
+        (&inputs[1], 1 << 20, false),
+        (&inputs[1], 4096, true),
+    ] {
+        let output = input.with_extension("log.gz");
+        let opts = CompressOptions {
+            level: 6,
+            buffer_size,
+            mmap,
+            reproducible: Some(EPOCH),
+            ..Default::default()
+        };
+        compress_path_with_options(input, &output, &opts).unwrap();
+        outputs.push(fs::read(&output).unwrap());
+    }
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(outputs.iter().all(|o| *o == outputs[0]));
+    let header = read_gz_header(&outputs[0][..]).unwrap();
+    assert_eq!(header.mtime, EPOCH);
+    assert_eq!(header.os, OS_UNKNOWN);
+    assert_eq!(header.flags, 0x08, "只应设置 FNAME");
+    assert_eq!(header.filename_lossy().as_deref(), Some("data.log"));
+    assert_eq!(
+        hex(&outputs[0]),
+        "c48674508c9be999bb6f469bce4aa2d901ee9b968d4135bc5d888deb96f1198e"
+    );
+}
+
+#[test]
+fn test_reproducible_stream_output() {
+    let data = content();
+    let opts = CompressOptions {
+        level: 1,
+        reproducible: Some(0),
+        ..Default::default()
+    };
+    let whole = compress_reader_to_writer_with_options(&data[..], Vec::new(), &opts).unwrap();
+    let short =
+        compress_reader_to_writer_with_options(ShortReads(&data, 0), Vec::new(), &opts).unwrap();
+    assert_eq!(whole, short);
+    let header = read_gz_header(&whole[..]).unwrap();
+    assert_eq!((header.mtime, header.os, header.flags), (0, OS_UNKNOWN, 0));
+    assert_eq!(
+        hex(&whole),
+        "55cee129f667ff949c4dcfbd7229b750dfbdd0d1b5a2292a2c910019564fa54c"
+    );
+}
@@ -0,0 +1,99 @@
+//! 基于 proptest 的往返测试：任意输入在各压缩级别下都能无损还原，分卷不超过上限，
+//! 任意字节输入不会 panic。
+
+use proptest::prelude::*;
+use rgzip::{
+    compress_reader_split, compress_reader_to_writer, compress_reader_to_writer_with_options,
+    decompress_reader_to_writer, read_gz_header, CompressOptions, MIN_SPLIT_SIZE,
+};
+
+fn decompress(gz: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    decompress_reader_to_writer(gz, &mut out).unwrap();
+    out
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn split_volumes_bounded(
+        text in proptest::collection::vec(0u8..4, 0..150_000),
+        noise in proptest::collection::vec(any::<u8>(), 0..60_000),
+        level in 0u32..=9,
+        extra in 0u64..20_000,
+    ) {
+        let data: Vec<u8> = [&text[..], &noise[..], &text[..]].concat();
+        let split_size = MIN_SPLIT_SIZE + extra;
+        let dir = tempdir_for(level, extra);
+        let base = dir.join("v.gz");
+        let volumes = compress_reader_split(&data[..], &base, &CompressOptions::with_level(level), split_size).unwrap();
+        let mut joined = Vec::new();
+        for v in &volumes {
+            let bytes = std::fs::read(v).unwrap();
+            prop_assert!(bytes.len() as u64 <= split_size);
+            joined.extend(bytes);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+        prop_assert_eq!(decompress(&joined), data);
+    }
+}
+
+fn tempdir_for(level: u32, extra: u64) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rgzip-prop-split-{}-{}-{}",
+        std::process::id(),
+        level,
+        extra
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+proptest! {
+    #[test]
+    fn roundtrip_all_levels(data in proptest::collection::vec(any::<u8>(), 0..8192), level in 0u32..=9) {
+        let gz = compress_reader_to_writer(&data[..], Vec::new(), level).unwrap();
+        prop_assert_eq!(decompress(&gz), data);
+    }
+
+    #[test]
+    fn roundtrip_small_buffers(data in proptest::collection::vec(any::<u8>(), 0..4096), buffer_size in 1usize..64) {
+        let opts = CompressOptions { buffer_size, ..Default::default() };
+        let gz = compress_reader_to_writer_with_options(&data[..], Vec::new(), &opts).unwrap();
+        prop_assert_eq!(decompress(&gz), data);
+    }
+
+    #[test]
+    fn multi_member_concatenation(parts in proptest::collection::vec(proptest::collection::vec(any::<u8>(), 0..1024), 1..5)) {
+        let mut joined = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            joined.extend(compress_reader_to_writer(&part[..], Vec::new(), i as u32 % 10).unwrap());
+        }
+        prop_assert_eq!(decompress(&joined), parts.concat());
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+        let _ = decompress_reader_to_writer(&data[..], Vec::new());
+        let _ = read_gz_header(&data[..]);
+    }
+
+    #[test]
+    fn corrupted_member_never_panics(data in proptest::collection::vec(any::<u8>(), 1..1024), pos in any::<prop::sample::Index>(), byte in any::<u8>()) {
+        let mut gz = compress_reader_to_writer(&data[..], Vec::new(), 6).unwrap();
+        let i = pos.index(gz.len());
+        gz[i] ^= byte | 1;
+        // 篡改后偶尔仍是合法流（例如只改了 mtime），因此只要求不 panic。
+        let _ = decompress_reader_to_writer(&gz[..], Vec::new());
+    }
+}
+
+#[test]
+fn roundtrip_empty_input() {
+    for level in 0..=9 {
+        let gz = compress_reader_to_writer(&b""[..], Vec::new(), level).unwrap();
+        assert!(decompress(&gz).is_empty());
+        assert_eq!(read_gz_header(&gz[..]).unwrap().len, 10);
+    }
+}
@@ -210,8 +210,8 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(),
                         match key.code {
                             KeyCode::Char('q') => return Ok(()),
                             KeyCode::Up => app.selected = app.selected.saturating_sub(1),
-                            KeyCode::Down => {
-                                if app.selected + 1 < app.processes.len() { app.selected += 1; }
+                            KeyCode::Down if app.selected + 1 < app.processes.len() => {
+                                app.selected += 1;
                             }
                             KeyCode::PageUp => app.selected = app.selected.saturating_sub(10),
                             KeyCode::PageDown => {
@@ -250,13 +250,11 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(),
                             KeyCode::Enter | KeyCode::Char('d') => {
                                 app.show_details = !app.show_details;
                             }
-                            KeyCode::Esc => {
-                                if !app.filter.is_empty() {
-                                    app.filter.clear();
-                                    app.selected = 0;
-                                    app.processes = collect_processes(&sys);
-                                    app.sort_processes();
-                                }
+                            KeyCode::Esc if !app.filter.is_empty() => {
+                                app.filter.clear();
+                                app.selected = 0;
+                                app.processes = collect_processes(&sys);
+                                app.sort_processes();
                             }
                             _ => {}
                         }
@@ -272,10 +270,8 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(),
                                 app.sort_processes();
                             }
                             KeyCode::Backspace => { app.filter.pop(); }
-                            KeyCode::Char(c) => {
-                                // 只接受可显示字符
-                                if !c.is_control() { app.filter.push(c); }
-                            }
+                            // 只接受可显示字符
+                            KeyCode::Char(c) if !c.is_control() => { app.filter.push(c); }
                             _ => {}
                         }
 
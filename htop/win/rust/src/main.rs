@@ -160,8 +160,8 @@ impl App {
         self.mem_used_mb = self.sys.used_memory() / 1024;
 
         // 维护历史（百分比 0-100）
-        let cpu_pct = self.cpu_usage.max(0.0).min(100.0) as u64;
-        let mem_pct = if self.mem_total_mb == 0 { 0 } else { ((self.mem_used_mb * 100) / self.mem_total_mb).min(100) };
+        let cpu_pct = self.cpu_usage.clamp(0.0, 100.0) as u64;
+        let mem_pct = (self.mem_used_mb * 100).checked_div(self.mem_total_mb).unwrap_or(0).min(100);
         self.cpu_hist.push_back(cpu_pct);
         self.mem_hist.push_back(mem_pct);
         while self.cpu_hist.len() > self.hist_capacity {
@@ -210,7 +210,7 @@ impl App {
             SortKey::Cpu => rows.sort_by(|a, b| a.cpu.partial_cmp(&b.cpu).unwrap_or(std::cmp::Ordering::Equal)),
             SortKey::Mem => rows.sort_by_key(|r| r.mem_mb),
             SortKey::Pid => rows.sort_by_key(|r| r.pid.as_u32()),
-            SortKey::Name => rows.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
+            SortKey::Name => rows.sort_by_key(|r| r.name.to_lowercase()),
         }
         if self.sort_desc {
             rows.reverse();